@@ -0,0 +1,257 @@
+use age::armor::{ArmoredReader, ArmoredWriter, Format};
+use age::secrecy::SecretString;
+use age::{x25519, DecryptError, Decryptor, Encryptor};
+use bech32::{ToBase32, Variant};
+use std::io::{Read, Write};
+
+/// First line of a binary age v1 file.
+const BINARY_HEADER: &[u8] = b"age-encryption.org/v1\n";
+
+/// First line of an ASCII armored age file.
+const ARMOR_HEADER: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// Bech32 prefix of an age X25519 recipient, as in `age1...`.
+pub const RECIPIENT_PREFIX: &str = "age1";
+
+/// Bech32 prefix of an age X25519 identity, as in `AGE-SECRET-KEY-1...`.
+pub const IDENTITY_PREFIX: &str = "AGE-SECRET-KEY-1";
+
+const KEY_NUM_BYTES: usize = 32;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AgeError {
+    PrivateKeyWrongLength {
+        expected_length: usize,
+        actual_length: usize,
+    },
+    PublicKeyWrongLength {
+        expected_length: usize,
+        actual_length: usize,
+    },
+    /// Text starting `age1` is not a valid age X25519 recipient.
+    MalformedRecipient,
+    /// Text starting `AGE-SECRET-KEY-1` is not a valid age X25519 identity.
+    MalformedIdentity,
+    /// Neither a recipient nor a passphrase was given to encrypt to.
+    NoRecipients,
+    /// Both recipients and a passphrase were given, which age does not allow.
+    PassphraseWithRecipients,
+    /// File is not an age v1 file, or its header is damaged.
+    MalformedFile,
+    /// File is encrypted to a passphrase, but none was given.
+    PassphraseRequired,
+    /// None of the identities or passphrase given can open the file.
+    NoMatchingKeys,
+    /// File was changed after it was encrypted.
+    DecryptionFailed,
+}
+
+/// Whether `data` looks like an age file, binary or armored.
+pub fn is_age_file(data: &[u8]) -> bool {
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    data[start..].starts_with(BINARY_HEADER) || data[start..].starts_with(ARMOR_HEADER)
+}
+
+fn bech32_encode(prefix: &str, key: &[u8]) -> String {
+    let hrp = prefix.trim_end_matches('1').to_lowercase();
+    bech32::encode(&hrp, key.to_base32(), Variant::Bech32).expect("prefix is valid")
+}
+
+/// Write an X25519 public key as an age recipient.
+pub fn recipient_string(public_key: &[u8]) -> Result<String, AgeError> {
+    if public_key.len() != KEY_NUM_BYTES {
+        return Err(AgeError::PublicKeyWrongLength {
+            expected_length: KEY_NUM_BYTES,
+            actual_length: public_key.len(),
+        });
+    }
+    Ok(bech32_encode(RECIPIENT_PREFIX, public_key))
+}
+
+/// Write an X25519 private key as an age identity.
+pub fn identity_string(private_key: &[u8]) -> Result<String, AgeError> {
+    if private_key.len() != KEY_NUM_BYTES {
+        return Err(AgeError::PrivateKeyWrongLength {
+            expected_length: KEY_NUM_BYTES,
+            actual_length: private_key.len(),
+        });
+    }
+    Ok(bech32_encode(IDENTITY_PREFIX, private_key).to_uppercase())
+}
+
+pub fn parse_recipient(text: &str) -> Result<x25519::Recipient, AgeError> {
+    text.trim().parse().map_err(|_| AgeError::MalformedRecipient)
+}
+
+/// Read the identities in an age identity file, skipping comments and empty lines.
+pub fn parse_identities(text: &str) -> Result<Vec<x25519::Identity>, AgeError> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.parse().map_err(|_| AgeError::MalformedIdentity))
+        .collect()
+}
+
+/// Encrypt `data` to age recipients, or to a passphrase using an scrypt stanza.
+///
+/// age does not allow a passphrase to be mixed with recipients, so giving both is an error.
+pub fn encrypt(
+    recipients: &[x25519::Recipient],
+    passphrase: Option<&str>,
+    armor: bool,
+    data: &[u8],
+) -> Result<Vec<u8>, AgeError> {
+    let encryptor = match passphrase {
+        Some(_) if !recipients.is_empty() => return Err(AgeError::PassphraseWithRecipients),
+        Some(passphrase) => {
+            Encryptor::with_user_passphrase(SecretString::from(passphrase.to_string()))
+        }
+        None => Encryptor::with_recipients(
+            recipients.iter().map(|recipient| recipient as &dyn age::Recipient),
+        )
+        .map_err(|_| AgeError::NoRecipients)?,
+    };
+
+    let format = if armor {
+        Format::AsciiArmor
+    } else {
+        Format::Binary
+    };
+    let write = || -> std::io::Result<Vec<u8>> {
+        let output = ArmoredWriter::wrap_output(Vec::new(), format)?;
+        let mut writer = encryptor.wrap_output(output)?;
+        writer.write_all(data)?;
+        writer.finish()?.finish()
+    };
+    Ok(write().expect("writing to memory can not fail"))
+}
+
+/// Decrypt a binary or armored age file with X25519 identities, or with a passphrase if
+/// the file was encrypted to one.
+pub fn decrypt(
+    identities: &[x25519::Identity],
+    passphrase: Option<&str>,
+    data: &[u8],
+) -> Result<Vec<u8>, AgeError> {
+    let decryptor = Decryptor::new(ArmoredReader::new(data)).map_err(|e| match e {
+        DecryptError::DecryptionFailed | DecryptError::InvalidMac => AgeError::DecryptionFailed,
+        _ => AgeError::MalformedFile,
+    })?;
+
+    let result = if decryptor.is_scrypt() {
+        let passphrase = passphrase.ok_or(AgeError::PassphraseRequired)?;
+        let identity = age::scrypt::Identity::new(SecretString::from(passphrase.to_string()));
+        decryptor.decrypt(std::iter::once(&identity as &dyn age::Identity))
+    } else {
+        decryptor.decrypt(identities.iter().map(|identity| identity as &dyn age::Identity))
+    };
+    let mut reader = result.map_err(|e| match e {
+        DecryptError::NoMatchingKeys | DecryptError::KeyDecryptionFailed => {
+            AgeError::NoMatchingKeys
+        }
+        DecryptError::DecryptionFailed | DecryptError::InvalidMac => AgeError::DecryptionFailed,
+        _ => AgeError::MalformedFile,
+    })?;
+
+    let mut plain_text = Vec::new();
+    reader
+        .read_to_end(&mut plain_text)
+        .map_err(|_| AgeError::DecryptionFailed)?;
+    Ok(plain_text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryFrom;
+
+    const IDENTITY: &str =
+        "AGE-SECRET-KEY-1XMWWC06LY3EE5RYTXM9MFLAZ2U56JJJ36S0MYPDRWSVLUL66MV4QX3S7F6";
+
+    /// Recipient for `IDENTITY`.
+    const RECIPIENT: &str = "age1w3tyke4gev25vaxxsvcgqu4484rf6ejpmavs57p6yz6lhy2sfs5swrvwyn";
+
+    fn identity_bytes() -> Vec<u8> {
+        let (_, data, _) = bech32::decode(IDENTITY).unwrap();
+        bech32::FromBase32::from_base32(&data).unwrap()
+    }
+
+    #[test]
+    fn key_bytes_map_to_age_strings() {
+        let private_key = identity_bytes();
+        let public_key = x25519_dalek::PublicKey::from(&x25519_dalek::StaticSecret::from(
+            <[u8; 32]>::try_from(&private_key[..]).unwrap(),
+        ));
+
+        assert_eq!(IDENTITY, identity_string(&private_key).unwrap());
+        assert_eq!(RECIPIENT, recipient_string(public_key.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn can_round_trip_with_recipient() {
+        let recipients = vec![parse_recipient(RECIPIENT).unwrap()];
+        let identities = parse_identities(&format!("# created: today\n{}\n", IDENTITY)).unwrap();
+
+        for armor in [false, true] {
+            let cipher_text = encrypt(&recipients, None, armor, b"hello age").unwrap();
+            assert!(is_age_file(&cipher_text));
+            assert_eq!(b"hello age".to_vec(), decrypt(&identities, None, &cipher_text).unwrap());
+        }
+    }
+
+    #[test]
+    fn armored_files_are_text() {
+        let recipients = vec![parse_recipient(RECIPIENT).unwrap()];
+
+        let cipher_text = encrypt(&recipients, None, true, b"hello age").unwrap();
+
+        assert!(cipher_text.starts_with(ARMOR_HEADER));
+        assert!(cipher_text.is_ascii());
+    }
+
+    #[test]
+    fn can_round_trip_with_passphrase() {
+        let cipher_text = encrypt(&[], Some("purple monkey"), false, b"hello age").unwrap();
+
+        assert_eq!(AgeError::PassphraseRequired, decrypt(&[], None, &cipher_text).unwrap_err());
+        assert_eq!(
+            b"hello age".to_vec(),
+            decrypt(&[], Some("purple monkey"), &cipher_text).unwrap()
+        );
+    }
+
+    #[test]
+    fn wrong_identity_does_not_match() {
+        let recipients = vec![parse_recipient(RECIPIENT).unwrap()];
+        let other_identity = x25519::Identity::generate();
+
+        let cipher_text = encrypt(&recipients, None, false, b"hello age").unwrap();
+
+        assert_eq!(
+            AgeError::NoMatchingKeys,
+            decrypt(&[other_identity], None, &cipher_text).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn can_not_mix_passphrase_with_recipients() {
+        let recipients = vec![parse_recipient(RECIPIENT).unwrap()];
+
+        let result = encrypt(&recipients, Some("purple monkey"), false, b"hello age").unwrap_err();
+        assert_eq!(AgeError::PassphraseWithRecipients, result);
+    }
+
+    #[test]
+    fn can_not_encrypt_to_nobody() {
+        assert_eq!(AgeError::NoRecipients, encrypt(&[], None, false, b"hello age").unwrap_err());
+    }
+
+    #[test]
+    fn sigli_messages_are_not_age_files() {
+        assert!(!is_age_file(b"ZKCNU ZOSJI INMQH"));
+        assert!(is_age_file(b"\n-----BEGIN AGE ENCRYPTED FILE-----\n"));
+    }
+}
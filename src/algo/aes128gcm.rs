@@ -2,7 +2,10 @@ use aes_gcm::aead::{generic_array::GenericArray, Aead, NewAead};
 use aes_gcm::Aes128Gcm;
 use rand::{thread_rng, RngCore};
 
-use super::{generate_128_bit_key_data, AlgoError, Algorithm};
+use super::{
+    counter_nonce, generate_128_bit_key_data, push_counter_header, take_counter_header, AlgoError,
+    AlgoInfo, Algorithm,
+};
 
 pub struct Aes128GcmAlgorithm {}
 
@@ -26,17 +29,27 @@ impl Aes128GcmAlgorithm {
 }
 
 impl Algorithm for Aes128GcmAlgorithm {
+    fn info(&self) -> AlgoInfo {
+        AlgoInfo {
+            key_num_bytes: Some(KEY_NUM_BYTES),
+            nonce_num_bytes: 12,
+            tag_num_bytes: 16,
+            deterministic: false,
+            misuse_resistant: false,
+        }
+    }
+
     fn generate_key_data(&self) -> Vec<u8> {
         generate_128_bit_key_data()
     }
 
-    fn encrypt_data(&self, key: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+    fn encrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
         let cipher = Self::create_cipher(key)?;
         let mut nonce = [0u8; 12];
         thread_rng().fill_bytes(&mut nonce);
 
         cipher
-            .encrypt_in_place(GenericArray::from_slice(&nonce), &[], data)
+            .encrypt_in_place(GenericArray::from_slice(&nonce), aad, data)
             .map_err(|_| AlgoError::EncryptionFailed)?;
 
         data.extend_from_slice(&nonce);
@@ -44,15 +57,55 @@ impl Algorithm for Aes128GcmAlgorithm {
         Ok(())
     }
 
-    fn decrypt_data(&self, key: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
-        let cipher = Self::create_cipher(&key)?;
+    fn decrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        let cipher = Self::create_cipher(key)?;
+        if data.len() < 12 + 16 {
+            return Err(AlgoError::DecryptionFailed);
+        }
         let body_len = data.len() - 12;
         let mut nonce = [0u8; 12];
         nonce.copy_from_slice(&data[body_len..]);
 
         data.truncate(body_len);
         cipher
-            .decrypt_in_place(GenericArray::from_slice(&nonce), &[], data)
+            .decrypt_in_place(GenericArray::from_slice(&nonce), aad, data)
+            .map_err(|_| AlgoError::DecryptionFailed)?;
+
+        Ok(())
+    }
+
+    fn encrypt_data_with_counter(
+        &self,
+        key: &[u8],
+        sender_id: u8,
+        counter: u64,
+        aad: &[u8],
+        data: &mut Vec<u8>,
+    ) -> Result<(), AlgoError> {
+        let cipher = Self::create_cipher(key)?;
+        let nonce = counter_nonce(sender_id, counter);
+
+        cipher
+            .encrypt_in_place(GenericArray::from_slice(&nonce), aad, data)
+            .map_err(|_| AlgoError::EncryptionFailed)?;
+
+        push_counter_header(sender_id, counter, data);
+
+        Ok(())
+    }
+
+    fn decrypt_data_with_counter(
+        &self,
+        key: &[u8],
+        aad: &[u8],
+        data: &mut Vec<u8>,
+    ) -> Result<(), AlgoError> {
+        let cipher = Self::create_cipher(key)?;
+        let (sender_id, counter) = take_counter_header(data)?;
+        let nonce = counter_nonce(sender_id, counter);
+
+        cipher
+            .decrypt_in_place(GenericArray::from_slice(&nonce), aad, data)
             .map_err(|_| AlgoError::DecryptionFailed)?;
 
         Ok(())
@@ -73,9 +126,21 @@ mod test {
         let key = algo.generate_key_data();
         let mut data = raw_data();
 
-        algo.encrypt_data(&key, &mut data).unwrap();
+        algo.encrypt_data(&key, &[], &mut data).unwrap();
         assert_ne!(raw_data(), data);
-        algo.decrypt_data(&key, &mut data).unwrap();
+        algo.decrypt_data(&key, &[], &mut data).unwrap();
+        assert_eq!(raw_data(), data);
+    }
+
+    #[test]
+    fn can_round_trip_with_counter() {
+        let algo = Aes128GcmAlgorithm {};
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data_with_counter(&key, 1, 300, &[], &mut data).unwrap();
+        assert_eq!(raw_data().len() + 16 + 3, data.len());
+        algo.decrypt_data_with_counter(&key, &[], &mut data).unwrap();
         assert_eq!(raw_data(), data);
     }
 
@@ -91,18 +156,40 @@ mod test {
         let mut data1 = raw_data();
         let mut data2 = raw_data();
         let key = algo.generate_key_data();
-        algo.encrypt_data(&key, &mut data1).unwrap();
-        algo.encrypt_data(&key, &mut data2).unwrap();
+        algo.encrypt_data(&key, &[], &mut data1).unwrap();
+        algo.encrypt_data(&key, &[], &mut data2).unwrap();
         assert_ne!(data1, data2)
     }
 
+    #[test]
+    fn encrypting_with_different_counters_generates_different_cipher_texts() {
+        let algo = Aes128GcmAlgorithm {};
+        let mut data1 = raw_data();
+        let mut data2 = raw_data();
+        let key = algo.generate_key_data();
+        algo.encrypt_data_with_counter(&key, 1, 1, &[], &mut data1).unwrap();
+        algo.encrypt_data_with_counter(&key, 1, 2, &[], &mut data2).unwrap();
+        assert_ne!(data1[2..], data2[2..])
+    }
+
+    #[test]
+    fn can_not_decrypt_truncated_cipher_text() {
+        let algo = Aes128GcmAlgorithm {};
+        let key = algo.generate_key_data();
+
+        for len in 0..12 + 16 {
+            let result = algo.decrypt_data(&key, &[], &mut vec![0u8; len]).unwrap_err();
+            assert_eq!(AlgoError::DecryptionFailed, result);
+        }
+    }
+
     #[test]
     fn can_not_use_wrong_length_key() {
         let algo = Aes128GcmAlgorithm {};
         let mut data = raw_data();
         let key = &[0u8; 17];
 
-        let result = algo.encrypt_data(key, &mut data).unwrap_err();
+        let result = algo.encrypt_data(key, &[], &mut data).unwrap_err();
 
         assert_eq!(
             AlgoError::KeyWrongLength {
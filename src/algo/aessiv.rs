@@ -0,0 +1,135 @@
+use aes_siv::aead::{generic_array::GenericArray, KeyInit};
+use aes_siv::siv::Aes128Siv;
+
+use super::{generate_256_bit_key_data, AlgoError, AlgoInfo, Algorithm};
+
+pub struct AesSivAlgorithm {}
+
+pub const ALGO_NAME: &str = "aessiv";
+
+// AES-SIV splits the key in two, one half for CMAC and one half for CTR mode.
+const KEY_NUM_BYTES: usize = 32;
+
+
+impl AesSivAlgorithm {
+    // An empty header list keeps cipher texts without associated data unchanged.
+    fn headers(aad: &[u8]) -> Vec<&[u8]> {
+        if aad.is_empty() {
+            Vec::new()
+        } else {
+            vec![aad]
+        }
+    }
+
+    fn create_cipher(key: &[u8]) -> Result<Aes128Siv, AlgoError> {
+        if key.len() != KEY_NUM_BYTES {
+            return Err(AlgoError::KeyWrongLength {
+                expected_length: KEY_NUM_BYTES,
+                actual_length: key.len(),
+            });
+        };
+
+        Ok(Aes128Siv::new(GenericArray::from_slice(key)))
+    }
+}
+
+impl Algorithm for AesSivAlgorithm {
+    fn info(&self) -> AlgoInfo {
+        AlgoInfo {
+            key_num_bytes: Some(KEY_NUM_BYTES),
+            nonce_num_bytes: 0,
+            tag_num_bytes: 16,
+            deterministic: true,
+            misuse_resistant: true,
+        }
+    }
+
+    fn generate_key_data(&self) -> Vec<u8> {
+        generate_256_bit_key_data()
+    }
+
+    fn encrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        let mut cipher = Self::create_cipher(key)?;
+
+        cipher
+            .encrypt_in_place(Self::headers(aad), data)
+            .map_err(|_| AlgoError::EncryptionFailed)?;
+
+        Ok(())
+    }
+
+    fn decrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        let mut cipher = Self::create_cipher(key)?;
+
+        cipher
+            .decrypt_in_place(Self::headers(aad), data)
+            .map_err(|_| AlgoError::DecryptionFailed)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw_data() -> Vec<u8> {
+        vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43]
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let algo = AesSivAlgorithm {};
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data(&key, &[], &mut data).unwrap();
+        assert_ne!(raw_data(), data);
+        algo.decrypt_data(&key, &[], &mut data).unwrap();
+        assert_eq!(raw_data(), data);
+    }
+
+    #[test]
+    fn generates_different_keys() {
+        let algo = AesSivAlgorithm {};
+        assert_ne!(algo.generate_key_data(), algo.generate_key_data(), )
+    }
+
+    #[test]
+    fn encrypting_twice_generates_same_cipher_text() {
+        let algo = AesSivAlgorithm {};
+        let mut data1 = raw_data();
+        let mut data2 = raw_data();
+        let key = algo.generate_key_data();
+        algo.encrypt_data(&key, &[], &mut data1).unwrap();
+        algo.encrypt_data(&key, &[], &mut data2).unwrap();
+        assert_eq!(data1, data2)
+    }
+
+    #[test]
+    fn output_only_includes_tag() {
+        let algo = AesSivAlgorithm {};
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data(&key, &[], &mut data).unwrap();
+        assert_eq!(raw_data().len() + 16, data.len());
+    }
+
+    #[test]
+    fn can_not_use_wrong_length_key() {
+        let algo = AesSivAlgorithm {};
+        let mut data = raw_data();
+        let key = &[0u8; 31];
+
+        let result = algo.encrypt_data(key, &[], &mut data).unwrap_err();
+
+        assert_eq!(
+            AlgoError::KeyWrongLength {
+                expected_length: 32,
+                actual_length: 31
+            },
+            result
+        )
+    }
+}
@@ -0,0 +1,178 @@
+use ascon_aead::aead::{generic_array::GenericArray, AeadInPlace, KeyInit};
+use ascon_aead::Ascon128;
+use rand::{thread_rng, RngCore};
+
+use super::{generate_128_bit_key_data, AlgoError, AlgoInfo, Algorithm};
+
+pub struct Ascon128Algorithm {}
+
+pub const ALGO_NAME: &str = "ascon128";
+
+const KEY_NUM_BYTES: usize = 16;
+
+const NONCE_NUM_BYTES: usize = 16;
+
+impl Ascon128Algorithm {
+    fn create_cipher(key: &[u8]) -> Result<Ascon128, AlgoError> {
+        if key.len() != KEY_NUM_BYTES {
+            return Err(AlgoError::KeyWrongLength {
+                expected_length: KEY_NUM_BYTES,
+                actual_length: key.len(),
+            });
+        };
+
+        Ok(Ascon128::new(GenericArray::from_slice(key)))
+    }
+
+    fn encrypt_data_with_nonce(
+        key: &[u8],
+        nonce: &[u8; NONCE_NUM_BYTES],
+        aad: &[u8],
+        data: &mut Vec<u8>,
+    ) -> Result<(), AlgoError> {
+        let cipher = Self::create_cipher(key)?;
+
+        cipher
+            .encrypt_in_place(GenericArray::from_slice(nonce), aad, data)
+            .map_err(|_| AlgoError::EncryptionFailed)?;
+
+        data.extend_from_slice(nonce);
+
+        Ok(())
+    }
+}
+
+impl Algorithm for Ascon128Algorithm {
+    fn info(&self) -> AlgoInfo {
+        AlgoInfo {
+            key_num_bytes: Some(KEY_NUM_BYTES),
+            nonce_num_bytes: NONCE_NUM_BYTES,
+            tag_num_bytes: 16,
+            deterministic: false,
+            misuse_resistant: false,
+        }
+    }
+
+    fn generate_key_data(&self) -> Vec<u8> {
+        generate_128_bit_key_data()
+    }
+
+    fn encrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        let mut nonce = [0u8; NONCE_NUM_BYTES];
+        thread_rng().fill_bytes(&mut nonce);
+
+        Self::encrypt_data_with_nonce(key, &nonce, aad, data)
+    }
+
+    fn decrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        let cipher = Self::create_cipher(key)?;
+        if data.len() < NONCE_NUM_BYTES {
+            return Err(AlgoError::DecryptionFailed);
+        }
+        let body_len = data.len() - NONCE_NUM_BYTES;
+        let mut nonce = [0u8; NONCE_NUM_BYTES];
+        nonce.copy_from_slice(&data[body_len..]);
+
+        data.truncate(body_len);
+        cipher
+            .decrypt_in_place(GenericArray::from_slice(&nonce), aad, data)
+            .map_err(|_| AlgoError::DecryptionFailed)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw_data() -> Vec<u8> {
+        vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43]
+    }
+
+    // Key and nonce used by the Ascon-128 v1.2 known answer tests.
+    fn kat_key() -> Vec<u8> {
+        hex::decode("000102030405060708090A0B0C0D0E0F").unwrap()
+    }
+
+    fn kat_nonce() -> [u8; NONCE_NUM_BYTES] {
+        let mut nonce = [0u8; NONCE_NUM_BYTES];
+        nonce.copy_from_slice(&kat_key());
+        nonce
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let algo = Ascon128Algorithm {};
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data(&key, &[], &mut data).unwrap();
+        assert_ne!(raw_data(), data);
+        algo.decrypt_data(&key, &[], &mut data).unwrap();
+        assert_eq!(raw_data(), data);
+    }
+
+    #[test]
+    fn matches_known_answer_tests() {
+        let vectors = [
+            ("", "E355159F292911F794CB1432A0103A8A"),
+            ("00", "BC18C3F4E39ECA7222490D967C79BFFC92"),
+            ("000102", "BC820D5BCA14147915031C69F6B27848A7EE29"),
+        ];
+
+        for (plain_text, cipher_text) in vectors.iter() {
+            let mut data = hex::decode(plain_text).unwrap();
+            Ascon128Algorithm::encrypt_data_with_nonce(&kat_key(), &kat_nonce(), &[], &mut data)
+                .unwrap();
+
+            let mut expected = hex::decode(cipher_text).unwrap();
+            expected.extend_from_slice(&kat_nonce());
+            assert_eq!(expected, data);
+        }
+    }
+
+    #[test]
+    fn can_decrypt_known_answer_test() {
+        let algo = Ascon128Algorithm {};
+        let mut data = hex::decode("BC820D5BCA14147915031C69F6B27848A7EE29").unwrap();
+        data.extend_from_slice(&kat_nonce());
+
+        algo.decrypt_data(&kat_key(), &[], &mut data).unwrap();
+        assert_eq!(vec![0x00, 0x01, 0x02], data);
+    }
+
+    #[test]
+    fn generates_different_keys() {
+        let algo = Ascon128Algorithm {};
+        assert_ne!(algo.generate_key_data(), algo.generate_key_data(), )
+    }
+
+    #[test]
+    fn encrypting_twice_generates_different_cipher_texts() {
+        let algo = Ascon128Algorithm {};
+        let mut data1 = raw_data();
+        let mut data2 = raw_data();
+        let key = algo.generate_key_data();
+        algo.encrypt_data(&key, &[], &mut data1).unwrap();
+        algo.encrypt_data(&key, &[], &mut data2).unwrap();
+        assert_ne!(data1, data2)
+    }
+
+    #[test]
+    fn can_not_use_wrong_length_key() {
+        let algo = Ascon128Algorithm {};
+        let mut data = raw_data();
+        let key = &[0u8; 17];
+
+        let result = algo.encrypt_data(key, &[], &mut data).unwrap_err();
+
+        assert_eq!(
+            AlgoError::KeyWrongLength {
+                expected_length: 16,
+                actual_length: 17,
+            },
+            result
+        )
+    }
+}
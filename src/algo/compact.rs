@@ -0,0 +1,276 @@
+use aes::Aes256;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use rand::{thread_rng, RngCore};
+use sha2::{Digest, Sha256};
+
+use super::{generate_256_bit_key_data, AlgoError, AlgoInfo, Algorithm};
+
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+type HmacSha256 = Hmac<Sha256>;
+
+pub struct CompactAlgorithm {
+    pub tag_num_bytes: usize,
+}
+
+pub const ALGO_NAME: &str = "compact";
+
+pub const DEFAULT_TAG_NUM_BYTES: usize = 8;
+
+const MIN_TAG_NUM_BYTES: usize = 4;
+
+const MAX_TAG_NUM_BYTES: usize = 16;
+
+const KEY_NUM_BYTES: usize = 32;
+
+const NONCE_NUM_BYTES: usize = 4;
+
+const ENC_KEY_LABEL: &[u8] = b"sigli compact enc";
+
+const MAC_KEY_LABEL: &[u8] = b"sigli compact mac";
+
+impl CompactAlgorithm {
+    fn check_tag_length(&self) -> Result<(), AlgoError> {
+        if self.tag_num_bytes < MIN_TAG_NUM_BYTES || self.tag_num_bytes > MAX_TAG_NUM_BYTES {
+            return Err(AlgoError::TagWrongLength {
+                min_length: MIN_TAG_NUM_BYTES,
+                max_length: MAX_TAG_NUM_BYTES,
+                actual_length: self.tag_num_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Split the key into independent encryption and authentication keys.
+    fn derive_sub_keys(key: &[u8]) -> Result<([u8; 32], [u8; 32]), AlgoError> {
+        if key.len() != KEY_NUM_BYTES {
+            return Err(AlgoError::KeyWrongLength {
+                expected_length: KEY_NUM_BYTES,
+                actual_length: key.len(),
+            });
+        };
+
+        let derive = |label: &[u8]| {
+            let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+            mac.update(label);
+            let mut sub_key = [0u8; 32];
+            sub_key.copy_from_slice(&mac.finalize().into_bytes());
+            sub_key
+        };
+
+        Ok((derive(ENC_KEY_LABEL), derive(MAC_KEY_LABEL)))
+    }
+
+    /// Synthetic IV: a MAC over the nonce, any associated data and the plain text.
+    fn create_mac(mac_key: &[u8], nonce: &[u8], aad: &[u8], plain_text: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(mac_key).expect("HMAC accepts any key length");
+        mac.update(nonce);
+        // Length prefix keeps the boundary between associated data and plain text, so it is
+        // included even when there is no associated data.
+        mac.update(&(aad.len() as u64).to_be_bytes());
+        mac.update(aad);
+        mac.update(plain_text);
+        mac
+    }
+
+    /// The counter IV is bound to both the nonce and the truncated tag.
+    fn apply_key_stream(enc_key: &[u8], nonce: &[u8], tag: &[u8], data: &mut [u8]) {
+        let mut hash = Sha256::new();
+        hash.update(nonce);
+        hash.update(tag);
+        let iv = hash.finalize();
+
+        let mut cipher = Aes256Ctr::new(enc_key.into(), iv[..16].into());
+        cipher.apply_keystream(data);
+    }
+}
+
+impl Algorithm for CompactAlgorithm {
+    fn info(&self) -> AlgoInfo {
+        AlgoInfo {
+            key_num_bytes: Some(KEY_NUM_BYTES),
+            nonce_num_bytes: NONCE_NUM_BYTES,
+            tag_num_bytes: self.tag_num_bytes,
+            deterministic: false,
+            misuse_resistant: true,
+        }
+    }
+
+    fn generate_key_data(&self) -> Vec<u8> {
+        generate_256_bit_key_data()
+    }
+
+    fn encrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        self.check_tag_length()?;
+        let (enc_key, mac_key) = Self::derive_sub_keys(key)?;
+        let mut nonce = [0u8; NONCE_NUM_BYTES];
+        thread_rng().fill_bytes(&mut nonce);
+
+        let full_tag = Self::create_mac(&mac_key, &nonce, aad, data)
+            .finalize()
+            .into_bytes();
+        let tag = &full_tag[..self.tag_num_bytes];
+
+        Self::apply_key_stream(&enc_key, &nonce, tag, data);
+
+        data.extend_from_slice(tag);
+        data.extend_from_slice(&nonce);
+
+        Ok(())
+    }
+
+    fn decrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        self.check_tag_length()?;
+        let (enc_key, mac_key) = Self::derive_sub_keys(key)?;
+        if data.len() < self.tag_num_bytes + NONCE_NUM_BYTES {
+            return Err(AlgoError::DecryptionFailed);
+        }
+
+        let nonce_pos = data.len() - NONCE_NUM_BYTES;
+        let body_len = nonce_pos - self.tag_num_bytes;
+        let mut nonce = [0u8; NONCE_NUM_BYTES];
+        nonce.copy_from_slice(&data[nonce_pos..]);
+        let tag = data[body_len..nonce_pos].to_vec();
+
+        data.truncate(body_len);
+        Self::apply_key_stream(&enc_key, &nonce, &tag, data);
+
+        Self::create_mac(&mac_key, &nonce, aad, data)
+            .verify_truncated_left(&tag)
+            .map_err(|_| {
+                data.clear();
+                AlgoError::DecryptionFailed
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw_data() -> Vec<u8> {
+        vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43]
+    }
+
+    fn default_algo() -> CompactAlgorithm {
+        CompactAlgorithm {
+            tag_num_bytes: DEFAULT_TAG_NUM_BYTES,
+        }
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let algo = default_algo();
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data(&key, &[], &mut data).unwrap();
+        assert_ne!(raw_data(), data);
+        algo.decrypt_data(&key, &[], &mut data).unwrap();
+        assert_eq!(raw_data(), data);
+    }
+
+    #[test]
+    fn generates_different_keys() {
+        let algo = default_algo();
+        assert_ne!(algo.generate_key_data(), algo.generate_key_data(), )
+    }
+
+    #[test]
+    fn encrypting_twice_generates_different_cipher_texts() {
+        let algo = default_algo();
+        let mut data1 = raw_data();
+        let mut data2 = raw_data();
+        let key = algo.generate_key_data();
+        algo.encrypt_data(&key, &[], &mut data1).unwrap();
+        algo.encrypt_data(&key, &[], &mut data2).unwrap();
+        assert_ne!(data1, data2)
+    }
+
+    #[test]
+    fn output_includes_truncated_tag_and_short_nonce() {
+        let algo = CompactAlgorithm { tag_num_bytes: 6 };
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data(&key, &[], &mut data).unwrap();
+        assert_eq!(raw_data().len() + 6 + 4, data.len());
+    }
+
+    #[test]
+    fn can_not_decrypt_tampered_cipher_text() {
+        let algo = default_algo();
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data(&key, &[], &mut data).unwrap();
+        data[0] ^= 0x01;
+
+        let result = algo.decrypt_data(&key, &[], &mut data).unwrap_err();
+        assert_eq!(AlgoError::DecryptionFailed, result);
+    }
+
+    #[test]
+    fn mac_separates_aad_from_plain_text() {
+        let aad = b"to: bob";
+        let mut aad_as_plain_text = (aad.len() as u64).to_be_bytes().to_vec();
+        aad_as_plain_text.extend_from_slice(aad);
+        aad_as_plain_text.extend_from_slice(&raw_data());
+
+        let with_aad = CompactAlgorithm::create_mac(&[0u8; 32], &[0u8; 4], aad, &raw_data());
+        let without_aad =
+            CompactAlgorithm::create_mac(&[0u8; 32], &[0u8; 4], &[], &aad_as_plain_text);
+
+        assert_ne!(with_aad.finalize().into_bytes(), without_aad.finalize().into_bytes());
+    }
+
+    #[test]
+    fn can_not_decrypt_with_different_tag_length() {
+        let key = default_algo().generate_key_data();
+        let mut data = raw_data();
+
+        CompactAlgorithm { tag_num_bytes: 8 }
+            .encrypt_data(&key, &[], &mut data)
+            .unwrap();
+
+        let result = CompactAlgorithm { tag_num_bytes: 4 }
+            .decrypt_data(&key, &[], &mut data)
+            .unwrap_err();
+        assert_eq!(AlgoError::DecryptionFailed, result);
+    }
+
+    #[test]
+    fn can_not_use_out_of_range_tag_length() {
+        let algo = CompactAlgorithm { tag_num_bytes: 3 };
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        let result = algo.encrypt_data(&key, &[], &mut data).unwrap_err();
+
+        assert_eq!(
+            AlgoError::TagWrongLength {
+                min_length: 4,
+                max_length: 16,
+                actual_length: 3
+            },
+            result
+        )
+    }
+
+    #[test]
+    fn can_not_use_wrong_length_key() {
+        let algo = default_algo();
+        let mut data = raw_data();
+        let key = &[0u8; 31];
+
+        let result = algo.encrypt_data(key, &[], &mut data).unwrap_err();
+
+        assert_eq!(
+            AlgoError::KeyWrongLength {
+                expected_length: 32,
+                actual_length: 31
+            },
+            result
+        )
+    }
+}
@@ -2,22 +2,47 @@ use rand::{thread_rng, RngCore};
 
 mod aes128gcm;
 mod aes256gcm;
+mod aes128gcmsiv;
+mod aes256gcmsiv;
+mod aessiv;
+mod ascon128;
+mod chacha20poly1305;
+mod compact;
+mod otp;
+mod xchacha20poly1305;
 
 use std::str::FromStr;
 
+pub use self::compact::DEFAULT_TAG_NUM_BYTES as DEFAULT_COMPACT_TAG_NUM_BYTES;
+pub use self::otp::DEFAULT_PAD_NUM_BYTES;
+
+pub(crate) use self::otp::OtpAlgorithm;
+
 pub const DEFAULT_ALGO_NAME: &str = aes256gcm::ALGO_NAME;
 
-pub static ALGORITHM_NAMES: &[&str] = &[aes256gcm::ALGO_NAME, aes128gcm::ALGO_NAME];
+pub static ALGORITHM_NAMES: &[&str] = &[
+    aes256gcm::ALGO_NAME,
+    aes128gcm::ALGO_NAME,
+    chacha20poly1305::ALGO_NAME,
+    xchacha20poly1305::ALGO_NAME,
+    aes256gcmsiv::ALGO_NAME,
+    aes128gcmsiv::ALGO_NAME,
+    aessiv::ALGO_NAME,
+    compact::ALGO_NAME,
+    otp::ALGO_NAME,
+    ascon128::ALGO_NAME,
+];
 
 /// Format used to either encrypt or decrypt data.
+#[derive(Clone)]
 pub enum AlgoType {
 
     /// AES-GCM with 128bit Key.
     ///
     /// Implemented using [RustCrypto/AEADs]: https://github.com/RustCrypto/AEADs
     ///
-    /// Generates an output that is 16 bytes longer than the input.
-    /// - 4 bytes of authentication tag appended by algorithm.
+    /// Generates an output that is 28 bytes longer than the input.
+    /// - 16 bytes of authentication tag appended by algorithm.
     /// - 12 bytes of nonce append by this code.
     Aes128Gcm,
 
@@ -25,10 +50,123 @@ pub enum AlgoType {
     ///
     /// Implemented using [RustCrypto/AEADs]: https://github.com/RustCrypto/AEADs
     ///
-    /// Generates an output that is 16 bytes longer than the input.
-    /// - 4 bytes of authentication tag appended by algorithm.
+    /// Generates an output that is 28 bytes longer than the input.
+    /// - 16 bytes of authentication tag appended by algorithm.
     /// - 12 bytes of nonce append by this code.
     Aes256Gcm,
+
+    /// ChaCha20-Poly1305 with 256bit Key.
+    ///
+    /// Implemented using [RustCrypto/AEADs]: https://github.com/RustCrypto/AEADs
+    ///
+    /// Fast in software, so a good choice on machines without AES hardware support.
+    ///
+    /// Generates an output that is 28 bytes longer than the input.
+    /// - 16 bytes of authentication tag appended by algorithm.
+    /// - 12 bytes of nonce append by this code.
+    ChaCha20Poly1305,
+
+    /// XChaCha20-Poly1305 with 256bit Key.
+    ///
+    /// Implemented using [RustCrypto/AEADs]: https://github.com/RustCrypto/AEADs
+    ///
+    /// Uses an extended 24 byte nonce, so randomly generated nonces are safe
+    /// no matter how many messages are encrypted with the same key.
+    ///
+    /// Generates an output that is 40 bytes longer than the input.
+    /// - 16 bytes of authentication tag appended by algorithm.
+    /// - 24 bytes of nonce append by this code.
+    XChaCha20Poly1305,
+
+    /// AES-GCM-SIV with 128bit Key.
+    ///
+    /// Implemented using [RustCrypto/AEADs]: https://github.com/RustCrypto/AEADs
+    ///
+    /// Nonce misuse resistant. If the random nonce is ever repeated, the only thing
+    /// revealed is whether the two messages were identical.
+    ///
+    /// Generates an output that is 28 bytes longer than the input.
+    /// - 16 bytes of authentication tag appended by algorithm.
+    /// - 12 bytes of nonce append by this code.
+    Aes128GcmSiv,
+
+    /// AES-GCM-SIV with 256bit Key.
+    ///
+    /// Implemented using [RustCrypto/AEADs]: https://github.com/RustCrypto/AEADs
+    ///
+    /// Nonce misuse resistant. If the random nonce is ever repeated, the only thing
+    /// revealed is whether the two messages were identical.
+    ///
+    /// Generates an output that is 28 bytes longer than the input.
+    /// - 16 bytes of authentication tag appended by algorithm.
+    /// - 12 bytes of nonce append by this code.
+    Aes256GcmSiv,
+
+    /// AES-SIV (RFC 5297) with 256bit Key.
+    ///
+    /// Implemented using [RustCrypto/AEADs]: https://github.com/RustCrypto/AEADs
+    ///
+    /// **Deterministic.** No nonce is used, so the same message encrypted under the
+    /// same key always gives the same cipher text. This allows encrypted values to be
+    /// matched against each other, but also reveals to an observer when a message
+    /// is repeated. The key is split into two 128bit AES keys.
+    ///
+    /// Generates an output that is 16 bytes longer than the input.
+    /// - 16 bytes of synthetic IV (authentication tag) prepended by algorithm.
+    AesSiv,
+
+    /// Compact profile for messages that are transcribed by hand.
+    ///
+    /// AES-256 in counter mode with a synthetic IV taken from a truncated HMAC-SHA256
+    /// tag, using a 256bit Key.
+    ///
+    /// Generates an output that is `tag_num_bytes + 4` bytes longer than the input
+    /// (12 bytes with the default 8 byte tag).
+    /// - `tag_num_bytes` (4 to 16) bytes of truncated authentication tag.
+    /// - 4 bytes of nonce.
+    ///
+    /// # Security Tradeoff
+    ///
+    /// The shorter output is paid for with weaker guarantees:
+    /// - A forged message is accepted with probability `2^-(8 * tag_num_bytes)` per
+    ///   attempt (1 in 2^64 with an 8 byte tag, 1 in 2^32 with a 4 byte tag).
+    /// - The 4 byte nonce is expected to repeat after around 65,000 messages under the
+    ///   same key. Because the IV is synthetic a repeat only reveals whether two
+    ///   messages were identical, but keys should still be rotated well before then.
+    ///
+    /// Both sides must use the same tag length.
+    Compact { tag_num_bytes: usize },
+
+    /// One-time pad.
+    ///
+    /// The key is a large random pad shared in advance. Each message XORs the plain text
+    /// with pad bytes that have never been used before, so it is information-theoretically
+    /// secret as long as no pad byte is ever used twice. Authentication uses a one-time
+    /// Poly1305 key taken from separate pad bytes.
+    ///
+    /// Encryption needs to know which pad bytes are still unused, so must be done with
+    /// `encrypt_with_pad` rather than `encrypt`. Decryption uses `decrypt` as normal.
+    ///
+    /// Generates an output that is 17 or more bytes longer than the input.
+    /// - 1 or more bytes recording the pad offset (1 byte for offsets under 128).
+    /// - 16 bytes of authentication tag.
+    ///
+    /// Each message consumes 32 bytes of pad for the authentication key plus one pad byte
+    /// for each byte of input.
+    Otp,
+
+    /// Ascon-128 with 128bit Key.
+    ///
+    /// Implemented using [RustCrypto/AEADs]: https://github.com/RustCrypto/AEADs
+    ///
+    /// Lightweight algorithm from the NIST lightweight cryptography standard, suitable for
+    /// exchanging messages with microcontrollers. Matches the Ascon-128 v1.2 test vectors.
+    /// Devices must lay out messages as cipher text, then tag, then nonce.
+    ///
+    /// Generates an output that is 32 bytes longer than the input.
+    /// - 16 bytes of authentication tag appended by algorithm.
+    /// - 16 bytes of nonce append by this code.
+    Ascon128,
 }
 
 impl FromStr for AlgoType {
@@ -37,6 +175,16 @@ impl FromStr for AlgoType {
         match s {
             aes128gcm::ALGO_NAME => Ok(AlgoType::Aes128Gcm),
             aes256gcm::ALGO_NAME => Ok(AlgoType::Aes256Gcm),
+            chacha20poly1305::ALGO_NAME => Ok(AlgoType::ChaCha20Poly1305),
+            xchacha20poly1305::ALGO_NAME => Ok(AlgoType::XChaCha20Poly1305),
+            aes128gcmsiv::ALGO_NAME => Ok(AlgoType::Aes128GcmSiv),
+            aes256gcmsiv::ALGO_NAME => Ok(AlgoType::Aes256GcmSiv),
+            aessiv::ALGO_NAME => Ok(AlgoType::AesSiv),
+            compact::ALGO_NAME => Ok(AlgoType::Compact {
+                tag_num_bytes: compact::DEFAULT_TAG_NUM_BYTES,
+            }),
+            otp::ALGO_NAME => Ok(AlgoType::Otp),
+            ascon128::ALGO_NAME => Ok(AlgoType::Ascon128),
             _ => Err("no match"),
         }
     }
@@ -48,20 +196,91 @@ pub enum AlgoError {
         expected_length: usize,
         actual_length: usize,
     },
+    TagWrongLength {
+        min_length: usize,
+        max_length: usize,
+        actual_length: usize,
+    },
     EncryptionFailed,
     DecryptionFailed,
+    CounterNotSupported,
+    PadOffsetRequired,
+    PadExhausted {
+        pad_length: usize,
+        required_length: usize,
+    },
+}
+
+/// Facts about an algorithm, used to compare the cost of each one.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AlgoInfo {
+    /// Length of key. `None` when any length is accepted (one-time pads).
+    pub key_num_bytes: Option<usize>,
+    /// Length of nonce carried in each message (the minimum, when it varies).
+    pub nonce_num_bytes: usize,
+    /// Length of authentication tag carried in each message.
+    pub tag_num_bytes: usize,
+    /// Same message and key always gives the same cipher text.
+    pub deterministic: bool,
+    /// A repeated nonce only reveals whether two messages were identical.
+    pub misuse_resistant: bool,
+}
+
+impl AlgoInfo {
+    /// Number of bytes added to each message.
+    pub fn overhead_num_bytes(&self) -> usize {
+        self.nonce_num_bytes + self.tag_num_bytes
+    }
 }
 
 pub trait Algorithm {
+    fn info(&self) -> AlgoInfo;
     fn generate_key_data(&self) -> Vec<u8>;
-    fn encrypt_data(&self, key: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError>;
-    fn decrypt_data(&self, key: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError>;
+    fn encrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError>;
+    fn decrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError>;
+
+    /// Encrypt using a sender ID and message counter in place of a random nonce.
+    ///
+    /// Each sender sharing a key must use a different sender ID, and must never use the same
+    /// counter value twice with that key.
+    fn encrypt_data_with_counter(
+        &self,
+        _key: &[u8],
+        _sender_id: u8,
+        _counter: u64,
+        _aad: &[u8],
+        _data: &mut Vec<u8>,
+    ) -> Result<(), AlgoError> {
+        Err(AlgoError::CounterNotSupported)
+    }
+
+    /// Decrypt data produced by `encrypt_data_with_counter`.
+    fn decrypt_data_with_counter(
+        &self,
+        _key: &[u8],
+        _aad: &[u8],
+        _data: &mut Vec<u8>,
+    ) -> Result<(), AlgoError> {
+        Err(AlgoError::CounterNotSupported)
+    }
 }
 
 pub fn select_algorithm(name: AlgoType) -> Box<dyn Algorithm> {
     match name {
         AlgoType::Aes256Gcm => Box::new(aes256gcm::Aes256GcmAlgorithm {}),
         AlgoType::Aes128Gcm => Box::new(aes128gcm::Aes128GcmAlgorithm {}),
+        AlgoType::ChaCha20Poly1305 => Box::new(chacha20poly1305::ChaCha20Poly1305Algorithm {}),
+        AlgoType::XChaCha20Poly1305 => {
+            Box::new(xchacha20poly1305::XChaCha20Poly1305Algorithm {})
+        }
+        AlgoType::Aes128GcmSiv => Box::new(aes128gcmsiv::Aes128GcmSivAlgorithm {}),
+        AlgoType::Aes256GcmSiv => Box::new(aes256gcmsiv::Aes256GcmSivAlgorithm {}),
+        AlgoType::AesSiv => Box::new(aessiv::AesSivAlgorithm {}),
+        AlgoType::Compact { tag_num_bytes } => {
+            Box::new(compact::CompactAlgorithm { tag_num_bytes })
+        }
+        AlgoType::Otp => Box::new(otp::OtpAlgorithm {}),
+        AlgoType::Ascon128 => Box::new(ascon128::Ascon128Algorithm {}),
     }
 }
 
@@ -76,3 +295,163 @@ fn generate_128_bit_key_data() -> Vec<u8> {
     thread_rng().fill_bytes(&mut key_data);
     key_data.to_vec()
 }
+
+/// Expand a sender ID and message counter into a 96bit nonce.
+///
+/// The sender ID takes the first byte, so senders with different IDs never share a nonce even
+/// when their counters match.
+fn counter_nonce(sender_id: u8, counter: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[0] = sender_id;
+    nonce[4..].copy_from_slice(&counter.to_be_bytes());
+    nonce
+}
+
+/// Prefix data with the sender ID and counter needed to rebuild a counter nonce.
+fn push_counter_header(sender_id: u8, counter: u64, data: &mut Vec<u8>) {
+    push_counter_prefix(counter, data);
+    data.insert(0, sender_id);
+}
+
+/// Remove the header added by `push_counter_header`, returning the sender ID and counter.
+fn take_counter_header(data: &mut Vec<u8>) -> Result<(u8, u64), AlgoError> {
+    if data.is_empty() {
+        return Err(AlgoError::DecryptionFailed);
+    }
+    let sender_id = data.remove(0);
+    Ok((sender_id, take_counter_prefix(data)?))
+}
+
+/// Prefix data with the counter, using seven bits per byte so small counters stay short.
+pub(crate) fn push_counter_prefix(counter: u64, data: &mut Vec<u8>) {
+    let mut prefix = Vec::with_capacity(10);
+    let mut remaining = counter;
+    loop {
+        let point = (remaining & 0x7F) as u8;
+        remaining >>= 7;
+        if remaining == 0 {
+            prefix.push(point);
+            break;
+        }
+        prefix.push(point | 0x80);
+    }
+    data.splice(0..0, prefix);
+}
+
+/// Remove the counter prefix added by `push_counter_prefix`.
+pub(crate) fn take_counter_prefix(data: &mut Vec<u8>) -> Result<u64, AlgoError> {
+    let mut counter = 0u64;
+    for (idx, point) in data.iter().enumerate().take(10) {
+        counter |= u64::from(point & 0x7F) << (7 * idx);
+        if point & 0x80 == 0 {
+            data.drain(..=idx);
+            return Ok(counter);
+        }
+    }
+    Err(AlgoError::DecryptionFailed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reported_overhead_matches_output_length() {
+        for name in ALGORITHM_NAMES.iter().filter(|name| **name != otp::ALGO_NAME) {
+            let algo = select_algorithm(AlgoType::from_str(name).unwrap());
+            let key = algo.generate_key_data();
+            let mut data = vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43];
+
+            algo.encrypt_data(&key, &[], &mut data).unwrap();
+            assert_eq!(6 + algo.info().overhead_num_bytes(), data.len(), "{}", name);
+            assert_eq!(algo.info().key_num_bytes, Some(key.len()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn truncated_cipher_text_fails_to_decrypt() {
+        for name in ALGORITHM_NAMES.iter() {
+            let algo = select_algorithm(AlgoType::from_str(name).unwrap());
+            let key = algo.generate_key_data();
+
+            for len in 0..algo.info().overhead_num_bytes() {
+                let result = algo.decrypt_data(&key, &[], &mut vec![0u8; len]);
+                assert!(result.is_err(), "{} with {} bytes", name, len);
+            }
+        }
+    }
+
+    #[test]
+    fn can_not_decrypt_with_different_aad() {
+        for name in ALGORITHM_NAMES.iter().filter(|name| **name != otp::ALGO_NAME) {
+            let algo = select_algorithm(AlgoType::from_str(name).unwrap());
+            let key = algo.generate_key_data();
+            let mut data = vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43];
+
+            algo.encrypt_data(&key, b"header", &mut data).unwrap();
+            let mut copy = data.clone();
+            algo.decrypt_data(&key, b"header", &mut copy).unwrap();
+            assert_eq!(vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43], copy, "{}", name);
+
+            let result = algo.decrypt_data(&key, b"footer", &mut data).unwrap_err();
+            assert_eq!(AlgoError::DecryptionFailed, result, "{}", name);
+        }
+    }
+
+    #[test]
+    fn can_round_trip_counter_prefix() {
+        for counter in &[0, 1, 127, 128, 300, u64::MAX] {
+            let mut data = vec![0xAB, 0x01];
+            push_counter_prefix(*counter, &mut data);
+            assert_eq!(*counter, take_counter_prefix(&mut data).unwrap());
+            assert_eq!(vec![0xAB, 0x01], data);
+        }
+    }
+
+    #[test]
+    fn small_counters_use_one_byte() {
+        let mut data = Vec::new();
+        push_counter_prefix(127, &mut data);
+        assert_eq!(vec![0x7F], data);
+    }
+
+    #[test]
+    fn different_senders_never_share_a_counter_nonce() {
+        assert_ne!(counter_nonce(1, 7), counter_nonce(2, 7));
+        assert_ne!(counter_nonce(1, 0), counter_nonce(0, 1 << 56));
+    }
+
+    #[test]
+    fn senders_with_same_counter_give_different_cipher_texts() {
+        let names = ["aes256gcm", "aes128gcm", "chacha20poly1305", "aes256gcmsiv", "aes128gcmsiv"];
+        for name in &names {
+            let algo = select_algorithm(AlgoType::from_str(name).unwrap());
+            let key = algo.generate_key_data();
+            let mut data1 = vec![0xAB, 0x01, 0x02];
+            let mut data2 = data1.clone();
+
+            algo.encrypt_data_with_counter(&key, 1, 7, &[], &mut data1).unwrap();
+            algo.encrypt_data_with_counter(&key, 2, 7, &[], &mut data2).unwrap();
+            assert_ne!(data1[2..], data2[2..], "{}", name);
+
+            algo.decrypt_data_with_counter(&key, &[], &mut data2).unwrap();
+            assert_eq!(vec![0xAB, 0x01, 0x02], data2, "{}", name);
+        }
+    }
+
+    #[test]
+    fn can_round_trip_counter_header() {
+        let mut data = vec![0xAB];
+        push_counter_header(2, 300, &mut data);
+        assert_eq!(vec![2, 0xAC, 0x02, 0xAB], data);
+        assert_eq!((2, 300), take_counter_header(&mut data).unwrap());
+        assert_eq!(vec![0xAB], data);
+        assert_eq!(AlgoError::DecryptionFailed, take_counter_header(&mut Vec::new()).unwrap_err());
+    }
+
+    #[test]
+    fn can_not_take_truncated_counter_prefix() {
+        let mut data = vec![0x80, 0x80];
+        assert_eq!(AlgoError::DecryptionFailed, take_counter_prefix(&mut data).unwrap_err());
+    }
+}
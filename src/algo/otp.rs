@@ -0,0 +1,229 @@
+use poly1305::universal_hash::KeyInit;
+use poly1305::Poly1305;
+use rand::{thread_rng, RngCore};
+use subtle::ConstantTimeEq;
+
+use super::{push_counter_prefix, take_counter_prefix, AlgoError, AlgoInfo, Algorithm};
+
+pub struct OtpAlgorithm {}
+
+pub const ALGO_NAME: &str = "otp";
+
+pub const DEFAULT_PAD_NUM_BYTES: usize = 65536;
+
+const MAC_KEY_NUM_BYTES: usize = 32;
+
+const TAG_NUM_BYTES: usize = 16;
+
+impl OtpAlgorithm {
+    /// Number of pad bytes consumed by a message with `data_len` bytes of plain text.
+    pub fn pad_bytes_used(data_len: usize) -> usize {
+        MAC_KEY_NUM_BYTES + data_len
+    }
+
+    pub fn generate_pad_data(num_bytes: usize) -> Vec<u8> {
+        let mut pad_data = vec![0u8; num_bytes];
+        thread_rng().fill_bytes(&mut pad_data);
+        pad_data
+    }
+
+    /// Slice out the one-time MAC key and key stream for a message starting at `offset`.
+    fn select_pad_bytes(
+        pad: &[u8],
+        offset: u64,
+        data_len: usize,
+    ) -> Result<(&[u8], &[u8]), AlgoError> {
+        let required_length = offset.saturating_add(Self::pad_bytes_used(data_len) as u64);
+        if required_length > pad.len() as u64 {
+            return Err(AlgoError::PadExhausted {
+                pad_length: pad.len(),
+                required_length: required_length as usize,
+            });
+        }
+
+        let (mac_key, key_stream) =
+            pad[offset as usize..required_length as usize].split_at(MAC_KEY_NUM_BYTES);
+        Ok((mac_key, key_stream))
+    }
+
+    /// Tag covers any associated data and the offset prefix as well as the cipher text.
+    fn compute_tag(mac_key: &[u8], aad: &[u8], prefixed_cipher_text: &[u8]) -> [u8; TAG_NUM_BYTES] {
+        let mut mac_input = Vec::with_capacity(8 + aad.len() + prefixed_cipher_text.len());
+        // Length prefix keeps the boundary between associated data and cipher text, so it is
+        // included even when there is no associated data.
+        mac_input.extend_from_slice(&(aad.len() as u64).to_be_bytes());
+        mac_input.extend_from_slice(aad);
+        mac_input.extend_from_slice(prefixed_cipher_text);
+
+        let mut tag = [0u8; TAG_NUM_BYTES];
+        tag.copy_from_slice(&Poly1305::new(mac_key.into()).compute_unpadded(&mac_input));
+        tag
+    }
+
+    /// Encrypt using the unused pad bytes that start at `offset`.
+    ///
+    /// Returns the offset of the first pad byte left unused by this message. The caller
+    /// must record this and never encrypt with an earlier offset again.
+    pub fn encrypt_data_at_offset(
+        &self,
+        pad: &[u8],
+        offset: u64,
+        aad: &[u8],
+        data: &mut Vec<u8>,
+    ) -> Result<u64, AlgoError> {
+        let (mac_key, key_stream) = Self::select_pad_bytes(pad, offset, data.len())?;
+
+        for (point, key) in data.iter_mut().zip(key_stream) {
+            *point ^= key;
+        }
+
+        let next_offset = offset + Self::pad_bytes_used(data.len()) as u64;
+        push_counter_prefix(offset, data);
+        let tag = Self::compute_tag(mac_key, aad, data);
+        data.extend_from_slice(&tag);
+
+        Ok(next_offset)
+    }
+}
+
+impl Algorithm for OtpAlgorithm {
+    fn info(&self) -> AlgoInfo {
+        AlgoInfo {
+            key_num_bytes: None,
+            nonce_num_bytes: 1,
+            tag_num_bytes: TAG_NUM_BYTES,
+            deterministic: false,
+            misuse_resistant: false,
+        }
+    }
+
+    fn generate_key_data(&self) -> Vec<u8> {
+        Self::generate_pad_data(DEFAULT_PAD_NUM_BYTES)
+    }
+
+    fn encrypt_data(&self, _key: &[u8], _aad: &[u8], _data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        // Without a record of the pad bytes already used there is no safe offset.
+        Err(AlgoError::PadOffsetRequired)
+    }
+
+    fn decrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        if data.len() < TAG_NUM_BYTES {
+            return Err(AlgoError::DecryptionFailed);
+        }
+        let body_len = data.len() - TAG_NUM_BYTES;
+        let mut tag = [0u8; TAG_NUM_BYTES];
+        tag.copy_from_slice(&data[body_len..]);
+        data.truncate(body_len);
+
+        let mut cipher_text = data.clone();
+        let offset = take_counter_prefix(&mut cipher_text)?;
+        let (mac_key, key_stream) = Self::select_pad_bytes(key, offset, cipher_text.len())?;
+
+        if !bool::from(Self::compute_tag(mac_key, aad, data).ct_eq(&tag)) {
+            return Err(AlgoError::DecryptionFailed);
+        }
+
+        data.clear();
+        data.extend(cipher_text.iter().zip(key_stream).map(|(point, key)| point ^ key));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw_data() -> Vec<u8> {
+        vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43]
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let algo = OtpAlgorithm {};
+        let pad = algo.generate_key_data();
+        let mut data = raw_data();
+
+        let next_offset = algo.encrypt_data_at_offset(&pad, 200, &[], &mut data).unwrap();
+        assert_eq!(200 + 32 + 6, next_offset);
+        assert_ne!(raw_data(), data);
+        algo.decrypt_data(&pad, &[], &mut data).unwrap();
+        assert_eq!(raw_data(), data);
+    }
+
+    #[test]
+    fn generates_different_pads() {
+        let algo = OtpAlgorithm {};
+        assert_ne!(algo.generate_key_data(), algo.generate_key_data(), )
+    }
+
+    #[test]
+    fn generates_pads_of_requested_length() {
+        assert_eq!(1000, OtpAlgorithm::generate_pad_data(1000).len());
+    }
+
+    #[test]
+    fn can_not_encrypt_without_offset() {
+        let algo = OtpAlgorithm {};
+        let pad = algo.generate_key_data();
+        let mut data = raw_data();
+
+        let result = algo.encrypt_data(&pad, &[], &mut data).unwrap_err();
+        assert_eq!(AlgoError::PadOffsetRequired, result);
+    }
+
+    #[test]
+    fn can_not_decrypt_tampered_cipher_text() {
+        let algo = OtpAlgorithm {};
+        let pad = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data_at_offset(&pad, 0, &[], &mut data).unwrap();
+        data[2] ^= 0x01;
+
+        let result = algo.decrypt_data(&pad, &[], &mut data).unwrap_err();
+        assert_eq!(AlgoError::DecryptionFailed, result);
+    }
+
+    #[test]
+    fn can_not_decrypt_with_different_aad() {
+        let algo = OtpAlgorithm {};
+        let pad = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data_at_offset(&pad, 0, b"header", &mut data).unwrap();
+
+        let result = algo.decrypt_data(&pad, b"footer", &mut data).unwrap_err();
+        assert_eq!(AlgoError::DecryptionFailed, result);
+    }
+
+    #[test]
+    fn tag_separates_aad_from_cipher_text() {
+        let aad = b"header";
+        let mut aad_as_cipher_text = (aad.len() as u64).to_be_bytes().to_vec();
+        aad_as_cipher_text.extend_from_slice(aad);
+        aad_as_cipher_text.extend_from_slice(&raw_data());
+
+        assert_ne!(
+            OtpAlgorithm::compute_tag(&[7u8; 32], aad, &raw_data()),
+            OtpAlgorithm::compute_tag(&[7u8; 32], &[], &aad_as_cipher_text)
+        );
+    }
+
+    #[test]
+    fn can_not_use_more_pad_than_exists() {
+        let algo = OtpAlgorithm {};
+        let pad = OtpAlgorithm::generate_pad_data(40);
+        let mut data = raw_data();
+
+        let result = algo.encrypt_data_at_offset(&pad, 4, &[], &mut data).unwrap_err();
+
+        assert_eq!(
+            AlgoError::PadExhausted {
+                pad_length: 40,
+                required_length: 42
+            },
+            result
+        )
+    }
+}
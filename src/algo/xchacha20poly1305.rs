@@ -0,0 +1,151 @@
+use chacha20poly1305::aead::{generic_array::GenericArray, AeadInPlace, KeyInit};
+use chacha20poly1305::XChaCha20Poly1305;
+use rand::{thread_rng, RngCore};
+
+use super::{generate_256_bit_key_data, AlgoError, AlgoInfo, Algorithm};
+
+pub struct XChaCha20Poly1305Algorithm {}
+
+pub const ALGO_NAME: &str = "xchacha20poly1305";
+
+const KEY_NUM_BYTES: usize = 32;
+
+const NONCE_NUM_BYTES: usize = 24;
+
+const TAG_NUM_BYTES: usize = 16;
+
+impl XChaCha20Poly1305Algorithm {
+    fn create_cipher(key: &[u8]) -> Result<XChaCha20Poly1305, AlgoError> {
+        if key.len() != KEY_NUM_BYTES {
+            return Err(AlgoError::KeyWrongLength {
+                expected_length: KEY_NUM_BYTES,
+                actual_length: key.len(),
+            });
+        };
+
+        Ok(XChaCha20Poly1305::new(GenericArray::from_slice(key)))
+    }
+}
+
+impl Algorithm for XChaCha20Poly1305Algorithm {
+    fn info(&self) -> AlgoInfo {
+        AlgoInfo {
+            key_num_bytes: Some(KEY_NUM_BYTES),
+            nonce_num_bytes: NONCE_NUM_BYTES,
+            tag_num_bytes: TAG_NUM_BYTES,
+            deterministic: false,
+            misuse_resistant: false,
+        }
+    }
+
+    fn generate_key_data(&self) -> Vec<u8> {
+        generate_256_bit_key_data()
+    }
+
+    fn encrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        let cipher = Self::create_cipher(key)?;
+        let mut nonce = [0u8; NONCE_NUM_BYTES];
+        thread_rng().fill_bytes(&mut nonce);
+
+        cipher
+            .encrypt_in_place(GenericArray::from_slice(&nonce), aad, data)
+            .map_err(|_| AlgoError::EncryptionFailed)?;
+
+        data.extend_from_slice(&nonce);
+
+        Ok(())
+    }
+
+    fn decrypt_data(&self, key: &[u8], aad: &[u8], data: &mut Vec<u8>) -> Result<(), AlgoError> {
+        let cipher = Self::create_cipher(key)?;
+        if data.len() < NONCE_NUM_BYTES + TAG_NUM_BYTES {
+            return Err(AlgoError::DecryptionFailed);
+        }
+        let body_len = data.len() - NONCE_NUM_BYTES;
+        let mut nonce = [0u8; NONCE_NUM_BYTES];
+        nonce.copy_from_slice(&data[body_len..]);
+
+        data.truncate(body_len);
+        cipher
+            .decrypt_in_place(GenericArray::from_slice(&nonce), aad, data)
+            .map_err(|_| AlgoError::DecryptionFailed)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw_data() -> Vec<u8> {
+        vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43]
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let algo = XChaCha20Poly1305Algorithm {};
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data(&key, &[], &mut data).unwrap();
+        assert_ne!(raw_data(), data);
+        algo.decrypt_data(&key, &[], &mut data).unwrap();
+        assert_eq!(raw_data(), data);
+    }
+
+    #[test]
+    fn output_includes_tag_and_extended_nonce() {
+        let algo = XChaCha20Poly1305Algorithm {};
+        let key = algo.generate_key_data();
+        let mut data = raw_data();
+
+        algo.encrypt_data(&key, &[], &mut data).unwrap();
+        assert_eq!(raw_data().len() + 16 + 24, data.len());
+    }
+
+    #[test]
+    fn generates_different_keys() {
+        let algo = XChaCha20Poly1305Algorithm {};
+        assert_ne!(algo.generate_key_data(), algo.generate_key_data(), )
+    }
+
+    #[test]
+    fn encrypting_twice_generates_different_cipher_texts() {
+        let algo = XChaCha20Poly1305Algorithm {};
+        let mut data1 = raw_data();
+        let mut data2 = raw_data();
+        let key = algo.generate_key_data();
+        algo.encrypt_data(&key, &[], &mut data1).unwrap();
+        algo.encrypt_data(&key, &[], &mut data2).unwrap();
+        assert_ne!(data1, data2)
+    }
+
+    #[test]
+    fn can_not_decrypt_truncated_cipher_text() {
+        let algo = XChaCha20Poly1305Algorithm {};
+        let key = algo.generate_key_data();
+
+        for len in 0..NONCE_NUM_BYTES + TAG_NUM_BYTES {
+            let result = algo.decrypt_data(&key, &[], &mut vec![0u8; len]).unwrap_err();
+            assert_eq!(AlgoError::DecryptionFailed, result);
+        }
+    }
+
+    #[test]
+    fn can_not_use_wrong_length_key() {
+        let algo = XChaCha20Poly1305Algorithm {};
+        let mut data = raw_data();
+        let key = &[0u8; 31];
+
+        let result = algo.encrypt_data(key, &[], &mut data).unwrap_err();
+
+        assert_eq!(
+            AlgoError::KeyWrongLength {
+                expected_length: 32,
+                actual_length: 31
+            },
+            result
+        )
+    }
+}
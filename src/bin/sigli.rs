@@ -1,31 +1,110 @@
 use clap::{value_t, App, Arg, ArgMatches, SubCommand};
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io;
-use std::io::{Read, Write};
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
+use std::path::Path;
 
 use sigli::{
-    decrypt, encrypt, generate_key, AlgoType, FormatType, SigliError, ALGORITHM_NAMES,
-    ALL_FORMAT_NAMES, DEFAULT_ALGO_NAME, DEFAULT_CIPHER_FORMAT, DEFAULT_KEY_FORMAT,
-    DEFAULT_PLAIN_FORMAT, KEY_FORMAT_NAMES,
+    age_identity, age_recipient, algorithm_info, attach_sender_signature, check_sender_signature,
+    decrypt, decrypt_age, decrypt_as_recipient, decrypt_cascade, decrypt_from_many,
+    decrypt_from_many_as_recipient, decrypt_with_counter, decrypt_with_passphrase, derive_key,
+    derive_key_from_passphrase, encrypt, encrypt_age, encrypt_cascade, encrypt_to_many,
+    encrypt_to_recipient, encrypt_with_counter, encrypt_with_pad, encrypt_with_passphrase,
+    fingerprint, finish_handshake, finish_pake, generate_key, generate_key_pair, generate_pad,
+    generate_passphrase, generate_sign_key_pair, initiate_handshake, is_age_file,
+    is_ssh_private_key, is_ssh_public_key, kem_info, passphrase_entropy_bits, respond_to_handshake,
+    sign, sign_detached, ssh_private_key_is_encrypted, start_pake, verify, verify_detached,
+    x25519_from_ssh_private_key, x25519_from_ssh_public_key, AlgoType, Argon2Params,
+    FingerprintType, FormatType, KdfType, KemType, SigliError, SignType, ALGORITHM_NAMES,
+    ALL_FORMAT_NAMES, DEFAULT_ALGO_NAME, DEFAULT_CIPHER_FORMAT, DEFAULT_COMPACT_TAG_NUM_BYTES,
+    DEFAULT_FINGERPRINT_NAME, DEFAULT_KEM_NAME, DEFAULT_KEY_FORMAT, DEFAULT_PAD_NUM_BYTES,
+    DEFAULT_PASSPHRASE_NUM_WORDS, DEFAULT_PASSPHRASE_SEPARATOR, DEFAULT_PLAIN_FORMAT,
+    FINGERPRINT_NAMES, HANDSHAKE_MESSAGE_NUM_BYTES, KDF_NAMES, KEM_NAMES, KEY_FORMAT_NAMES,
+    PAKE_MESSAGE_NUM_BYTES,
 };
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const ALGORITHMS_CMD: &str = "algorithms";
 const GEN_KEY_CMD: &str = "genkey";
+const GEN_KEY_PAIR_CMD: &str = "genkeypair";
+const FINGERPRINT_CMD: &str = "fingerprint";
+const PAKE_CMD: &str = "pake";
+const HANDSHAKE_CMD: &str = "handshake";
+const INITIATE_CMD: &str = "initiate";
+const RESPOND_CMD: &str = "respond";
+const KEY_CMD: &str = "key";
+const FROM_PASSPHRASE_CMD: &str = "from-passphrase";
+const TO_AGE_CMD: &str = "to-age";
+const DERIVE_CMD: &str = "derive";
+const GEN_PAD_CMD: &str = "genpad";
+const GEN_PASS_CMD: &str = "genpass";
 const ENCRYPT_CMD: &str = "encrypt";
 const DECRYPT_CMD: &str = "decrypt";
+const GEN_SIGN_KEY_CMD: &str = "gensignkey";
+const SIGN_CMD: &str = "sign";
+const VERIFY_CMD: &str = "verify";
 const ALGO_ARG: &str = "algo";
+const TAG_LENGTH_ARG: &str = "taglength";
 const INPUT_ARG: &str = "input";
 const OUTPUT_ARG: &str = "output";
 const KEY_FILE_ARG: &str = "keyfile";
+const LABEL_ARG: &str = "label";
 const KEY_FORMAT_ARG: &str = "keyformat";
 const INPUT_FORMAT_ARG: &str = "inputformat";
 const OUTPUT_FORMAT_ARG: &str = "outputformat";
+const COUNTER_ARG: &str = "counter";
+const COUNTER_FILE_SUFFIX: &str = ".counter";
+const SENDER_ID_ARG: &str = "senderid";
+const PAD_SIZE_ARG: &str = "padsize";
+const PAD_USED_FILE_SUFFIX: &str = ".used";
+const AAD_ARG: &str = "aad";
+const AAD_FILE_ARG: &str = "aadfile";
+const CASCADE_ARG: &str = "cascade";
+const KEM_ARG: &str = "kem";
+const RECIPIENT_ARG: &str = "recipient";
+const MULTI_ARG: &str = "multi";
+const IDENTITY_ARG: &str = "identity";
+const PUBLIC_OUTPUT_ARG: &str = "publicoutput";
+const PASSPHRASE_ARG: &str = "passphrase";
+const PASSPHRASE_ENV_ARG: &str = "passphraseenv";
+const PASSPHRASE_FILE_ARG: &str = "passphrasefile";
+const ARGON2_PARAMS_ARG: &str = "argon2params";
+const KDF_ARG: &str = "kdf";
+const KDF_PARAMS_ARG: &str = "kdfparams";
+const SALT_ARG: &str = "salt";
+const SALT_FORMAT_ARG: &str = "saltformat";
+const NUM_WORDS_ARG: &str = "numwords";
+const SEPARATOR_ARG: &str = "separator";
+const DETACHED_ARG: &str = "detached";
+const SIGNATURE_ARG: &str = "signature";
+const SIGNATURE_FORMAT_ARG: &str = "signatureformat";
+const SIGN_KEY_ARG: &str = "signkey";
+const SENDER_ARG: &str = "sender";
+const STYLE_ARG: &str = "style";
+const CODE_ENV_ARG: &str = "codeenv";
+const CODE_FILE_ARG: &str = "codefile";
+const PEER_ARG: &str = "peer";
+const AGE_ARG: &str = "age";
+const ARMOR_ARG: &str = "armor";
 
+// Handshake messages are binary, which plain1 can not always carry unchanged.
+static EXCHANGE_FORMAT_NAMES: &[&str] = &["hex", "signal1", "raw"];
+
+// Fields are only read through `Debug` when `main` unwraps the error.
+#[allow(dead_code)]
 #[derive(Debug)]
 enum CliError {
     SigliError(SigliError),
     NoCommand,
+    MalformedStateFile,
+    CounterExhausted,
+    PassphraseEnvNotSet,
+    PassphraseMismatch,
+    EmptyPassphrase,
+    MalformedArgon2Params,
+    CodeEnvNotSet,
+    EmptyCode,
     Io(io::Error),
 }
 
@@ -64,13 +143,287 @@ fn read_input(c: &ArgMatches) -> Result<Vec<u8>, CliError> {
     }
 }
 
+fn read_aad(c: &ArgMatches) -> Result<Vec<u8>, CliError> {
+    if let Some(file_name) = c.value_of(AAD_FILE_ARG) {
+        read_file(file_name)
+    } else {
+        Ok(c.value_of(AAD_ARG).unwrap_or("").as_bytes().to_vec())
+    }
+}
+
+/// Read the passphrase from whichever source was given, if any.
+///
+/// When prompting for a passphrase to encrypt with, it is asked for twice to catch typing
+/// mistakes.
+fn read_passphrase(c: &ArgMatches, confirm: bool) -> Result<Option<Vec<u8>>, CliError> {
+    let passphrase = if let Some(name) = c.value_of(PASSPHRASE_ENV_ARG) {
+        std::env::var(name).map_err(|_| CliError::PassphraseEnvNotSet)?
+    } else if let Some(file_name) = c.value_of(PASSPHRASE_FILE_ARG) {
+        let data = String::from_utf8_lossy(&read_file(file_name)?).into_owned();
+        data.trim_end_matches(&['\r', '\n'][..]).to_string()
+    } else if c.is_present(PASSPHRASE_ARG) {
+        let passphrase = rpassword::prompt_password("Passphrase: ")?;
+        if confirm && rpassword::prompt_password("Repeat passphrase: ")? != passphrase {
+            return Err(CliError::PassphraseMismatch);
+        }
+        passphrase
+    } else {
+        return Ok(None);
+    };
+
+    if passphrase.is_empty() {
+        return Err(CliError::EmptyPassphrase);
+    }
+    Ok(Some(passphrase.into_bytes()))
+}
+
+fn read_code(c: &ArgMatches) -> Result<Vec<u8>, CliError> {
+    let code = if let Some(name) = c.value_of(CODE_ENV_ARG) {
+        std::env::var(name).map_err(|_| CliError::CodeEnvNotSet)?
+    } else if let Some(file_name) = c.value_of(CODE_FILE_ARG) {
+        let data = String::from_utf8_lossy(&read_file(file_name)?).into_owned();
+        data.trim_end_matches(&['\r', '\n'][..]).to_string()
+    } else {
+        rpassword::prompt_password("Code word: ")?
+    };
+
+    if code.is_empty() {
+        return Err(CliError::EmptyCode);
+    }
+    Ok(code.into_bytes())
+}
+
+/// Read a key exchange message from stdin.
+///
+/// Text formats are read up to the first empty line after some text, or the end of input.
+/// Raw messages have no line ends, so exactly `num_bytes` bytes are read instead.
+fn read_exchange_message(
+    input_format: &FormatType,
+    num_bytes: usize,
+) -> Result<Vec<u8>, CliError> {
+    if let FormatType::Raw = input_format {
+        let mut message = vec![0u8; num_bytes];
+        io::stdin().read_exact(&mut message)?;
+        return Ok(message);
+    }
+
+    let mut message = Vec::new();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            if message.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if !message.is_empty() {
+            message.push(b'\n');
+        }
+        message.extend_from_slice(line.as_bytes());
+    }
+    Ok(message)
+}
+
+/// Write a key exchange message to stdout, ending text formats with an empty line.
+fn write_exchange_message(output_format: &FormatType, message: &[u8]) -> Result<(), CliError> {
+    write_stdout(message)?;
+    if !matches!(output_format, FormatType::Raw) {
+        if !message.ends_with(b"\n") {
+            write_stdout(b"\n")?;
+        }
+        write_stdout(b"\n")?;
+    }
+    io::stdout().flush()?;
+    Ok(())
+}
+
+fn read_public_key(public_key: &str) -> Result<Vec<u8>, CliError> {
+    if Path::new(public_key).is_file() {
+        read_file(public_key)
+    } else {
+        Ok(public_key.as_bytes().to_vec())
+    }
+}
+
+fn read_argon2_params(c: &ArgMatches) -> Result<Argon2Params, CliError> {
+    let values: Vec<u32> = c
+        .value_of(ARGON2_PARAMS_ARG)
+        .unwrap()
+        .split(',')
+        .map(|value| value.trim().parse().map_err(|_| CliError::MalformedArgon2Params))
+        .collect::<Result<_, _>>()?;
+
+    match values.as_slice() {
+        [memory_kib, iterations, parallelism] => Ok(Argon2Params {
+            memory_kib: *memory_kib,
+            iterations: *iterations,
+            parallelism: *parallelism,
+        }),
+        _ => Err(CliError::MalformedArgon2Params),
+    }
+}
+
 fn read_key_data(c: &ArgMatches) -> Result<Vec<u8>, CliError> {
-    let file_name = c.value_of(KEY_FILE_ARG).unwrap();
-    read_file(file_name)
+    single_key(read_all_key_data(c)?)
+}
+
+fn single_key(mut keys: Vec<Vec<u8>>) -> Result<Vec<u8>, CliError> {
+    if keys.len() != 1 {
+        return Err(SigliError::KeyCountMismatch {
+            expected_count: 1,
+            actual_count: keys.len(),
+        }
+        .into());
+    }
+    Ok(keys.remove(0))
+}
+
+fn read_all_key_data(c: &ArgMatches) -> Result<Vec<Vec<u8>>, CliError> {
+    c.values_of(KEY_FILE_ARG).into_iter().flatten().map(read_file).collect()
+}
+
+/// Read private keys from genkeypair, converting any OpenSSH Ed25519 private keys to x25519.
+///
+/// Prompts for the passphrase of encrypted OpenSSH keys.
+fn read_identity_key_data(
+    key_format: &FormatType,
+    c: &ArgMatches,
+) -> Result<Vec<Vec<u8>>, CliError> {
+    c.values_of(KEY_FILE_ARG)
+        .into_iter()
+        .flatten()
+        .map(|file_name| {
+            let data = read_file(file_name)?;
+            if !is_ssh_private_key(&data) {
+                return Ok(data);
+            }
+            let passphrase = if ssh_private_key_is_encrypted(&data)? {
+                Some(rpassword::prompt_password(format!("Passphrase for {}: ", file_name))?)
+            } else {
+                None
+            };
+            Ok(x25519_from_ssh_private_key(
+                key_format.clone(),
+                &data,
+                passphrase.as_ref().map(String::as_bytes),
+            )?)
+        })
+        .collect()
+}
+
+fn read_recipient_key_data(c: &ArgMatches) -> Result<Vec<Vec<u8>>, CliError> {
+    c.values_of(RECIPIENT_ARG)
+        .into_iter()
+        .flatten()
+        .map(read_public_key)
+        .collect()
+}
+
+/// Read recipients' public keys, converting any OpenSSH Ed25519 public keys to x25519.
+fn read_x25519_recipient_key_data(
+    key_format: &FormatType,
+    c: &ArgMatches,
+) -> Result<Vec<Vec<u8>>, CliError> {
+    read_recipient_key_data(c)?
+        .into_iter()
+        .map(|public_key| {
+            if is_ssh_public_key(&public_key) {
+                Ok(x25519_from_ssh_public_key(
+                    key_format.clone(),
+                    &public_key,
+                )?)
+            } else {
+                Ok(public_key)
+            }
+        })
+        .collect()
+}
+
+/// Whether to encrypt once for several keys, which is implied by giving more than one.
+///
+/// Cascades, counters and one-time pads each take their own keys, so they never imply it.
+fn encrypt_to_many_keys(c: &ArgMatches, algo_type: &AlgoType) -> bool {
+    let num_keys = c.occurrences_of(KEY_FILE_ARG) + c.occurrences_of(RECIPIENT_ARG);
+    let own_keys = c.is_present(CASCADE_ARG)
+        || c.is_present(COUNTER_ARG)
+        || matches!(algo_type, AlgoType::Otp);
+    c.is_present(MULTI_ARG) || (!own_keys && num_keys > 1)
+}
+
+/// Decrypt a message for one key with `single`, or for several keys with `many`.
+///
+/// Encrypt switches to the format for several keys by itself when given more than one, so
+/// without --multi a message that does not open with `single` is tried with `many` before
+/// giving up with the error from `single`.
+fn decrypt_single_or_many<S, M>(
+    c: &ArgMatches,
+    data: &mut Vec<u8>,
+    single: S,
+    many: M,
+) -> Result<(), CliError>
+where
+    S: FnOnce(&mut Vec<u8>) -> Result<(), SigliError>,
+    M: FnOnce(&mut Vec<u8>) -> Result<(), SigliError>,
+{
+    if c.is_present(MULTI_ARG) {
+        return Ok(many(data)?);
+    }
+
+    let cipher_text = data.clone();
+    if let Err(e) = single(data) {
+        *data = cipher_text;
+        many(data).map_err(|_| e)?;
+    }
+    Ok(())
+}
+
+/// State file kept next to a key file, holding the next unused value.
+///
+/// The file stays locked until it is dropped, and new values are flushed to disk before
+/// they are relied on, so concurrent or interrupted runs never reuse a value.
+struct StateFile {
+    file: File,
+}
+
+impl StateFile {
+    fn open(c: &ArgMatches, suffix: &str) -> Result<(StateFile, u64), CliError> {
+        let state_file_name = format!("{}{}", c.value_of(KEY_FILE_ARG).unwrap(), suffix);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(state_file_name)?;
+        file.lock()?;
+
+        let mut text = String::new();
+        file.read_to_string(&mut text)?;
+        let value = match text.trim() {
+            "" => 0,
+            value => value.parse::<u64>().map_err(|_| CliError::MalformedStateFile)?,
+        };
+
+        Ok((StateFile { file }, value))
+    }
+
+    fn store(&mut self, next_value: u64) -> Result<(), CliError> {
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        writeln!(self.file, "{}", next_value)?;
+        self.file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Take the next message counter from the state file kept next to the key file.
+fn reserve_counter(c: &ArgMatches) -> Result<u64, CliError> {
+    let (mut state, counter) = StateFile::open(c, COUNTER_FILE_SUFFIX)?;
+    state.store(counter.checked_add(1).ok_or(CliError::CounterExhausted)?)?;
+    Ok(counter)
 }
 
 fn write_stdout(data: &[u8]) -> Result<(), CliError> {
-    std::io::stdout().write_all(&data)?;
+    std::io::stdout().write_all(data)?;
     Ok(())
 }
 
@@ -82,13 +435,117 @@ fn write_file(file_name: &str, data: &[u8]) -> Result<(), CliError> {
 
 fn write_output(c: &ArgMatches, data: &[u8]) -> Result<(), CliError> {
     if let Some(file_name) = c.value_of(OUTPUT_ARG) {
-        write_file(file_name, &data)
+        write_file(file_name, data)
     } else {
-        write_stdout(&data)
+        write_stdout(data)
+    }
+}
+
+/// Format of plain text, which is raw for age files unless given, since age users expect
+/// the file contents unchanged.
+fn plain_format(c: &ArgMatches, format_arg: &str, age: bool) -> FormatType {
+    if age && c.occurrences_of(format_arg) == 0 {
+        FormatType::Raw
+    } else {
+        value_t!(c.value_of(format_arg), FormatType).unwrap()
+    }
+}
+
+fn algo_type_named(m: &ArgMatches, name: &str) -> AlgoType {
+    let mut algo_type = name.parse::<AlgoType>().unwrap();
+    if let AlgoType::Compact { tag_num_bytes } = &mut algo_type {
+        *tag_num_bytes = value_t!(m.value_of(TAG_LENGTH_ARG), usize).unwrap_or_else(|e| e.exit());
+    }
+    algo_type
+}
+
+/// Algorithms named by --cascade, in the order they are applied, or `None` without it.
+fn cascade_algo_types(m: &ArgMatches, c: &ArgMatches) -> Option<Vec<AlgoType>> {
+    c.values_of(CASCADE_ARG)
+        .map(|names| names.map(|name| algo_type_named(m, name)).collect())
+}
+
+/// Number of `signal1` letters needed to carry a number of bytes.
+fn signal1_letters(num_bytes: usize) -> usize {
+    (num_bytes as f64 * 8.0 / 26f64.log2()).ceil() as usize
+}
+
+fn signal1_groups(num_bytes: usize) -> usize {
+    signal1_letters(num_bytes).div_ceil(5)
+}
+
+fn describe_kem_sizes() -> String {
+    let sizes: Vec<String> = KEM_NAMES
+        .iter()
+        .map(|name| {
+            let info = kem_info(name.parse().unwrap());
+            format!(
+                "{} public keys are {} bytes ({} signal1 groups) and each message carries a {} \
+                 byte encapsulated key ({} signal1 groups)",
+                name,
+                info.public_key_num_bytes,
+                signal1_groups(info.public_key_num_bytes),
+                info.encapsulated_key_num_bytes,
+                signal1_groups(info.encapsulated_key_num_bytes)
+            )
+        })
+        .collect();
+    format!("Key encapsulation mechanism. {}.", sizes.join("; "))
+}
+
+fn format_algorithm_table(m: &ArgMatches) -> Vec<u8> {
+    let mut rows = vec![format!(
+        "{:<18} {:>4} {:>6} {:>4} {:>9} {:>8}  {}",
+        "NAME", "KEY", "NONCE", "TAG", "OVERHEAD", "SIGNAL1", "PROPERTIES"
+    )];
+
+    for name in ALGORITHM_NAMES {
+        let info = algorithm_info(algo_type_named(m, name));
+
+        let key_length = match info.key_num_bytes {
+            Some(key_num_bytes) => key_num_bytes.to_string(),
+            None => "pad".to_string(),
+        };
+
+        let mut properties = Vec::new();
+        if info.deterministic {
+            properties.push("deterministic");
+        }
+        if info.misuse_resistant {
+            properties.push("misuse-resistant");
+        }
+
+        rows.push(format!(
+            "{:<18} {:>4} {:>6} {:>4} {:>9} {:>8}  {}",
+            name,
+            key_length,
+            info.nonce_num_bytes,
+            info.tag_num_bytes,
+            info.overhead_num_bytes(),
+            signal1_letters(info.overhead_num_bytes()),
+            properties.join(", ")
+        ));
+    }
+
+    let mut table = String::new();
+    for row in rows {
+        table.push_str(row.trim_end());
+        table.push('\n');
     }
+
+    table.into_bytes()
 }
 
 fn body() -> Result<(), CliError> {
+    let default_tag_length = DEFAULT_COMPACT_TAG_NUM_BYTES.to_string();
+    let default_pad_size = DEFAULT_PAD_NUM_BYTES.to_string();
+    let default_num_words = DEFAULT_PASSPHRASE_NUM_WORDS.to_string();
+    let kem_help = describe_kem_sizes();
+    let default_argon2 = Argon2Params::default();
+    let default_argon2_params = format!(
+        "{},{},{}",
+        default_argon2.memory_kib, default_argon2.iterations, default_argon2.parallelism
+    );
     let m = App::new("Cipher CLI")
         .version(VERSION)
         .author("Richard Sunderland <richard@sunderlandfamily.info>")
@@ -99,7 +556,16 @@ fn body() -> Result<(), CliError> {
             .value_name("ALGORITHM_NAME")
             .possible_values(ALGORITHM_NAMES)
             .default_value(DEFAULT_ALGO_NAME)
-            .help("Name of algorithm.")
+            .help("Name of algorithm. Note that aessiv is deterministic: the same message \
+                   encrypted with the same key always gives the same cipher text.")
+        )
+        .arg(Arg::with_name(TAG_LENGTH_ARG)
+            .long("--tag-length")
+            .short("t")
+            .value_name("BYTES")
+            .default_value(&default_tag_length)
+            .help("Length of truncated authentication tag used by the compact algorithm (4 to 16). \
+                   Shorter tags give shorter messages but are easier to forge.")
         )
         .arg(Arg::with_name(KEY_FORMAT_ARG)
             .long("--key-format")
@@ -109,6 +575,10 @@ fn body() -> Result<(), CliError> {
             .default_value(DEFAULT_KEY_FORMAT)
             .help("Name of format.")
         )
+        .subcommand(SubCommand::with_name(ALGORITHMS_CMD)
+            .about("list algorithms with their key length and the bytes and signal1 letters \
+                    they add to each message")
+        )
         .subcommand(SubCommand::with_name(GEN_KEY_CMD)
             .about("generate new key")
             .arg(Arg::with_name(OUTPUT_ARG)
@@ -116,14 +586,274 @@ fn body() -> Result<(), CliError> {
                 .short("o")
                 .value_name("KEY_FILE")
                 .required(false)
-                .help("Output file containing generated key. If absent output is written to stdout."))
+                .help("Output file containing generated key. If absent output is written to stdout. \
+                       The key fingerprint is always written to stderr."))
+        )
+        .subcommand(SubCommand::with_name(FINGERPRINT_CMD)
+            .about("print a short fingerprint of a key to read aloud, to check both sides hold \
+                    the same key")
+            .arg(Arg::with_name(KEY_FILE_ARG)
+                .value_name("KEY_FILE")
+                .required(true)
+                .help("File containing key data. Any key, shared, public or private, can be \
+                       fingerprinted."))
+            .arg(Arg::with_name(STYLE_ARG)
+                .long("--style")
+                .short("s")
+                .value_name("STYLE")
+                .possible_values(FINGERPRINT_NAMES)
+                .default_value(DEFAULT_FINGERPRINT_NAME)
+                .help("Four groups of five letters (about 94 bits), or six words (about 77 \
+                       bits)."))
+        )
+        .subcommand(SubCommand::with_name(PAKE_CMD)
+            .about("agree a full strength key with someone who knows the same code word, by \
+                    exchanging one short signal1 message each way")
+            .arg(Arg::with_name(OUTPUT_ARG)
+                .long("--output")
+                .short("o")
+                .value_name("KEY_FILE")
+                .required(true)
+                .help("Output file containing agreed key."))
+            .arg(Arg::with_name(CODE_ENV_ARG)
+                .long("--code-env")
+                .value_name("VARIABLE")
+                .help("Read the code word from an environment variable instead of prompting."))
+            .arg(Arg::with_name(CODE_FILE_ARG)
+                .long("--code-file")
+                .value_name("CODE_FILE")
+                .conflicts_with(CODE_ENV_ARG)
+                .help("Read the code word from the first line of a file instead of prompting."))
+        )
+        .subcommand(SubCommand::with_name(HANDSHAKE_CMD)
+            .about("agree a fresh session key with a contact whose x25519 public key we hold, \
+                    using a Noise KK handshake")
+            .subcommand(SubCommand::with_name(INITIATE_CMD)
+                .about("send the first handshake message, then read the response from stdin")
+                .arg(Arg::with_name(KEY_FILE_ARG)
+                    .value_name("PRIVATE_KEY_FILE")
+                    .required(true)
+                    .help("File containing our x25519 private key from genkeypair."))
+                .arg(Arg::with_name(PEER_ARG)
+                    .long("--peer")
+                    .short("p")
+                    .value_name("PUBLIC_KEY")
+                    .required(true)
+                    .help("Responder's x25519 public key, or a file containing it."))
+                .arg(Arg::with_name(INPUT_FORMAT_ARG)
+                    .long("--input-format")
+                    .short("I")
+                    .value_name("FORMAT_NAME")
+                    .possible_values(EXCHANGE_FORMAT_NAMES)
+                    .default_value(DEFAULT_CIPHER_FORMAT)
+                    .help("Format of the response read from stdin."))
+                .arg(Arg::with_name(OUTPUT_FORMAT_ARG)
+                    .long("--output-format")
+                    .short("O")
+                    .value_name("FORMAT_NAME")
+                    .possible_values(EXCHANGE_FORMAT_NAMES)
+                    .default_value(DEFAULT_CIPHER_FORMAT)
+                    .help("Format of the first message written to stdout."))
+                .arg(Arg::with_name(OUTPUT_ARG)
+                    .long("--output")
+                    .short("o")
+                    .value_name("KEY_FILE")
+                    .required(true)
+                    .help("Output file containing session key."))
+            )
+            .subcommand(SubCommand::with_name(RESPOND_CMD)
+                .about("read the first handshake message from stdin, then send the response")
+                .arg(Arg::with_name(KEY_FILE_ARG)
+                    .value_name("PRIVATE_KEY_FILE")
+                    .required(true)
+                    .help("File containing our x25519 private key from genkeypair."))
+                .arg(Arg::with_name(PEER_ARG)
+                    .long("--peer")
+                    .short("p")
+                    .value_name("PUBLIC_KEY")
+                    .required(true)
+                    .help("Initiator's x25519 public key, or a file containing it."))
+                .arg(Arg::with_name(INPUT_FORMAT_ARG)
+                    .long("--input-format")
+                    .short("I")
+                    .value_name("FORMAT_NAME")
+                    .possible_values(EXCHANGE_FORMAT_NAMES)
+                    .default_value(DEFAULT_CIPHER_FORMAT)
+                    .help("Format of the first message read from stdin."))
+                .arg(Arg::with_name(OUTPUT_FORMAT_ARG)
+                    .long("--output-format")
+                    .short("O")
+                    .value_name("FORMAT_NAME")
+                    .possible_values(EXCHANGE_FORMAT_NAMES)
+                    .default_value(DEFAULT_CIPHER_FORMAT)
+                    .help("Format of the response written to stdout."))
+                .arg(Arg::with_name(OUTPUT_ARG)
+                    .long("--output")
+                    .short("o")
+                    .value_name("KEY_FILE")
+                    .required(true)
+                    .help("Output file containing session key."))
+            )
+        )
+        .subcommand(SubCommand::with_name(GEN_KEY_PAIR_CMD)
+            .about("generate new private and public key pair for public-key encryption")
+            .arg(Arg::with_name(OUTPUT_ARG)
+                .long("--output")
+                .short("o")
+                .value_name("PRIVATE_KEY_FILE")
+                .required(true)
+                .help("Output file containing generated private key."))
+            .arg(Arg::with_name(PUBLIC_OUTPUT_ARG)
+                .long("--public-output")
+                .short("p")
+                .value_name("PUBLIC_KEY_FILE")
+                .required(false)
+                .help("Output file containing generated public key. If absent output is written \
+                       to stdout."))
+            .arg(Arg::with_name(KEM_ARG)
+                .long("--kem")
+                .value_name("KEM_NAME")
+                .possible_values(KEM_NAMES)
+                .default_value(DEFAULT_KEM_NAME)
+                .help(&kem_help))
+        )
+        .subcommand(SubCommand::with_name(KEY_CMD)
+            .about("create key files in other ways")
+            .subcommand(SubCommand::with_name(FROM_PASSPHRASE_CMD)
+                .about("derive a key from a passphrase and salt, so that systems sharing the \
+                        passphrase arrive at the same key")
+                .arg(Arg::with_name(KDF_ARG)
+                    .long("--kdf")
+                    .value_name("KDF_NAME")
+                    .possible_values(KDF_NAMES)
+                    .required(true)
+                    .help("Key derivation function."))
+                .arg(Arg::with_name(KDF_PARAMS_ARG)
+                    .long("--params")
+                    .value_name("PARAMS")
+                    .required(true)
+                    .help("Comma separated parameters: ITERATIONS for pbkdf2 (for example \
+                           600000), LOG_N,R,P for scrypt (for example 17,8,1)."))
+                .arg(Arg::with_name(SALT_ARG)
+                    .long("--salt")
+                    .value_name("SALT")
+                    .required(true)
+                    .help("Salt agreed with the other systems using this key."))
+                .arg(Arg::with_name(SALT_FORMAT_ARG)
+                    .long("--salt-format")
+                    .value_name("FORMAT_NAME")
+                    .possible_values(ALL_FORMAT_NAMES)
+                    .default_value("raw")
+                    .help("Salt format. Use hex for a binary salt."))
+                .arg(Arg::with_name(PASSPHRASE_ARG)
+                    .long("--passphrase")
+                    .required_unless_one(&[PASSPHRASE_ENV_ARG, PASSPHRASE_FILE_ARG])
+                    .help("Prompts for the passphrase."))
+                .arg(Arg::with_name(PASSPHRASE_ENV_ARG)
+                    .long("--passphrase-env")
+                    .value_name("VARIABLE")
+                    .conflicts_with(PASSPHRASE_ARG)
+                    .help("Read the passphrase from an environment variable."))
+                .arg(Arg::with_name(PASSPHRASE_FILE_ARG)
+                    .long("--passphrase-file")
+                    .value_name("PASSPHRASE_FILE")
+                    .conflicts_with_all(&[PASSPHRASE_ARG, PASSPHRASE_ENV_ARG])
+                    .help("Read the passphrase from the first line of a file."))
+                .arg(Arg::with_name(OUTPUT_ARG)
+                    .long("--output")
+                    .short("o")
+                    .value_name("KEY_FILE")
+                    .required(false)
+                    .help("Output file containing derived key. If absent output is written to \
+                           stdout."))
+            )
+            .subcommand(SubCommand::with_name(TO_AGE_CMD)
+                .about("write an x25519 public key as an age recipient, or a private key as an \
+                        age identity")
+                .arg(Arg::with_name(KEY_FILE_ARG)
+                    .value_name("KEY_FILE")
+                    .required(true)
+                    .help("File containing a key from genkeypair."))
+                .arg(Arg::with_name(IDENTITY_ARG)
+                    .long("--identity")
+                    .help("KEY_FILE is a private key, to write as an age identity."))
+                .arg(Arg::with_name(OUTPUT_ARG)
+                    .long("--output")
+                    .short("o")
+                    .value_name("AGE_KEY_FILE")
+                    .required(false)
+                    .help("Output file containing the age key. If absent output is written to \
+                           stdout."))
+            )
+        )
+        .subcommand(SubCommand::with_name(DERIVE_CMD)
+            .about("derive a separate key for each contact, channel or purpose from a master key")
+            .arg(Arg::with_name(KEY_FILE_ARG)
+                .value_name("MASTER_KEY_FILE")
+                .required(true)
+                .help("File containing master key data."))
+            .arg(Arg::with_name(LABEL_ARG)
+                .long("--label")
+                .short("l")
+                .value_name("LABEL")
+                .required(true)
+                .help("Context label. Each label gives a different key, the same label always \
+                       gives the same key."))
+            .arg(Arg::with_name(OUTPUT_ARG)
+                .long("--output")
+                .short("o")
+                .value_name("KEY_FILE")
+                .required(false)
+                .help("Output file containing derived key. If absent output is written to \
+                       stdout."))
+        )
+        .subcommand(SubCommand::with_name(GEN_PAD_CMD)
+            .about("generate new one-time pad for the otp algorithm")
+            .arg(Arg::with_name(PAD_SIZE_ARG)
+                .long("--size")
+                .short("s")
+                .value_name("BYTES")
+                .default_value(&default_pad_size)
+                .help("Length of pad. Each message uses 32 bytes of pad plus its own length."))
+            .arg(Arg::with_name(OUTPUT_ARG)
+                .long("--output")
+                .short("o")
+                .value_name("PAD_FILE")
+                .required(false)
+                .help("Output file containing generated pad. If absent output is written to stdout."))
+        )
+        .subcommand(SubCommand::with_name(GEN_PASS_CMD)
+            .about("generate a memorable passphrase of random words, for use with --passphrase")
+            .arg(Arg::with_name(NUM_WORDS_ARG)
+                .long("--words")
+                .short("w")
+                .value_name("NUM_WORDS")
+                .default_value(&default_num_words)
+                .help("Number of words. Each word adds about 12.9 bits of entropy."))
+            .arg(Arg::with_name(SEPARATOR_ARG)
+                .long("--separator")
+                .short("s")
+                .value_name("TEXT")
+                .default_value(DEFAULT_PASSPHRASE_SEPARATOR)
+                .help("Text placed between words."))
+            .arg(Arg::with_name(OUTPUT_ARG)
+                .long("--output")
+                .short("o")
+                .value_name("PASSPHRASE_FILE")
+                .required(false)
+                .help("Output file containing generated passphrase. If absent output is written \
+                       to stdout. The entropy estimate is always written to stderr."))
         )
         .subcommand(SubCommand::with_name(ENCRYPT_CMD)
             .about("encrypt a message with an existing cipher")
             .arg(Arg::with_name(KEY_FILE_ARG)
                 .value_name("KEY_FILE")
-                .required(true)
-                .help("File containing key data"))
+                .required_unless_one(&[RECIPIENT_ARG, PASSPHRASE_ARG, PASSPHRASE_ENV_ARG,
+                    PASSPHRASE_FILE_ARG])
+                .multiple(true)
+                .help("File containing key data. With --cascade give one key file for each \
+                       algorithm, in the same order. Otherwise each key file given can decrypt \
+                       the message."))
 
             .arg(Arg::with_name(INPUT_ARG)
                 .long("--input")
@@ -153,13 +883,109 @@ fn body() -> Result<(), CliError> {
                 .default_value(DEFAULT_CIPHER_FORMAT)
                 .help("Cipher text format.")
             )
+            .arg(Arg::with_name(COUNTER_ARG)
+                .long("--counter")
+                .short("c")
+                .requires(SENDER_ID_ARG)
+                .help("Use a sender ID and message counter instead of a random nonce. The \
+                       counter is kept in KEY_FILE.counter and is shorter than a random nonce."))
+            .arg(Arg::with_name(SENDER_ID_ARG)
+                .long("--sender-id")
+                .value_name("ID")
+                .requires(COUNTER_ARG)
+                .help("Sender ID from 0 to 255, needed with --counter. Everyone sending counter \
+                       messages with the same key must use a different ID, or messages can be \
+                       decrypted and forged."))
+            .arg(Arg::with_name(CASCADE_ARG)
+                .long("--cascade")
+                .value_name("ALGORITHM_NAMES")
+                .possible_values(ALGORITHM_NAMES)
+                .use_delimiter(true)
+                .require_delimiter(true)
+                .conflicts_with(COUNTER_ARG)
+                .help("Comma separated algorithms to apply one after another, each with its own \
+                       key file. The first algorithm is applied first."))
+            .arg(Arg::with_name(RECIPIENT_ARG)
+                .long("--recipient")
+                .short("r")
+                .value_name("PUBLIC_KEY")
+                .multiple(true)
+                .number_of_values(1)
+                .conflicts_with_all(&[CASCADE_ARG, COUNTER_ARG])
+                .help("Encrypt under a fresh key that only the owner of the public key can \
+                       recover. Give the public key itself or a file containing it, which may \
+                       be an OpenSSH ssh-ed25519 public key. Repeat, or combine with KEY_FILE, \
+                       to encrypt for several keys."))
+            .arg(Arg::with_name(MULTI_ARG)
+                .long("--multi")
+                .conflicts_with_all(&[CASCADE_ARG, COUNTER_ARG])
+                .help("Encrypt once under a random data key, and wrap the data key for each \
+                       KEY_FILE and --recipient. Implied when more than one is given."))
+            .arg(Arg::with_name(PASSPHRASE_ARG)
+                .long("--passphrase")
+                .conflicts_with_all(&[KEY_FILE_ARG, CASCADE_ARG, COUNTER_ARG, RECIPIENT_ARG])
+                .help("Derive the key from a passphrase with Argon2id instead of using KEY_FILE. \
+                       The random salt and cost parameters are included in the cipher text. Prompts for the passphrase."))
+            .arg(Arg::with_name(PASSPHRASE_ENV_ARG)
+                .long("--passphrase-env")
+                .value_name("VARIABLE")
+                .conflicts_with_all(&[KEY_FILE_ARG, CASCADE_ARG, COUNTER_ARG, RECIPIENT_ARG])
+                .conflicts_with(PASSPHRASE_ARG)
+                .help("As --passphrase, but read the passphrase from an environment variable."))
+            .arg(Arg::with_name(PASSPHRASE_FILE_ARG)
+                .long("--passphrase-file")
+                .value_name("PASSPHRASE_FILE")
+                .conflicts_with_all(&[KEY_FILE_ARG, CASCADE_ARG, COUNTER_ARG, RECIPIENT_ARG])
+                .conflicts_with_all(&[PASSPHRASE_ARG, PASSPHRASE_ENV_ARG])
+                .help("As --passphrase, but read the passphrase from the first line of a file."))
+            .arg(Arg::with_name(ARGON2_PARAMS_ARG)
+                .long("--argon2-params")
+                .value_name("MEMORY_KIB,ITERATIONS,PARALLELISM")
+                .default_value(&default_argon2_params)
+                .help("Argon2id cost parameters used with a passphrase, at most \
+                       262144,16,8. Higher values make guessing the passphrase slower."))
+            .arg(Arg::with_name(KEM_ARG)
+                .long("--kem")
+                .value_name("KEM_NAME")
+                .possible_values(KEM_NAMES)
+                .default_value(DEFAULT_KEM_NAME)
+                .help(&kem_help))
+            .arg(Arg::with_name(SIGN_KEY_ARG)
+                .long("--sign-key")
+                .value_name("PRIVATE_SIGN_KEY_FILE")
+                .help("Sign the plain text with this private key from gensignkey before \
+                       encrypting, so recipients can check who sent it with decrypt --sender."))
+            .arg(Arg::with_name(AAD_ARG)
+                .long("--aad")
+                .value_name("TEXT")
+                .conflicts_with(AAD_FILE_ARG)
+                .help("Associated data that is authenticated but not encrypted. The same \
+                       associated data must be supplied to decrypt."))
+            .arg(Arg::with_name(AAD_FILE_ARG)
+                .long("--aad-file")
+                .value_name("AAD_FILE")
+                .help("File containing associated data, read as raw bytes."))
+            .arg(Arg::with_name(AGE_ARG)
+                .long("--age")
+                .conflicts_with_all(&[KEY_FILE_ARG, CASCADE_ARG, COUNTER_ARG, MULTI_ARG, AAD_ARG,
+                    AAD_FILE_ARG, SIGN_KEY_ARG])
+                .help("Write an age v1 file that age users can open, encrypted to each \
+                       --recipient (an age1... recipient or an x25519 public key) or to the \
+                       passphrase. Input is read as raw unless --input-format is given, and \
+                       --output-format is not used."))
+            .arg(Arg::with_name(ARMOR_ARG)
+                .long("--armor")
+                .requires(AGE_ARG)
+                .help("Write the age file with ASCII armor."))
         )
         .subcommand(SubCommand::with_name(DECRYPT_CMD)
             .about("decrypt a message with an existing cipher")
             .arg(Arg::with_name(KEY_FILE_ARG)
                 .value_name("KEY_FILE")
-                .required(true)
-                .help("File containing key data"))
+                .required_unless_one(&[PASSPHRASE_ARG, PASSPHRASE_ENV_ARG, PASSPHRASE_FILE_ARG])
+                .multiple(true)
+                .help("File containing key data. With --cascade give one key file for each \
+                       algorithm, in the same order."))
             .arg(Arg::with_name(INPUT_ARG)
                 .long("--input")
                 .short("i")
@@ -188,49 +1014,662 @@ fn body() -> Result<(), CliError> {
                 .default_value(DEFAULT_PLAIN_FORMAT)
                 .help("Plain text format.")
             )
+            .arg(Arg::with_name(COUNTER_ARG)
+                .long("--counter")
+                .short("c")
+                .help("Cipher text was encrypted with a message counter instead of a random nonce."))
+            .arg(Arg::with_name(CASCADE_ARG)
+                .long("--cascade")
+                .value_name("ALGORITHM_NAMES")
+                .possible_values(ALGORITHM_NAMES)
+                .use_delimiter(true)
+                .require_delimiter(true)
+                .conflicts_with(COUNTER_ARG)
+                .help("Comma separated algorithms used to encrypt, in the same order as given \
+                       to encrypt. Layers are removed in reverse order."))
+            .arg(Arg::with_name(IDENTITY_ARG)
+                .long("--identity")
+                .conflicts_with_all(&[CASCADE_ARG, COUNTER_ARG])
+                .help("KEY_FILE is a private key from genkeypair or an OpenSSH ed25519 \
+                       private key, and the cipher text was encrypted with --recipient. \
+                       Prompts for the passphrase of an encrypted OpenSSH key."))
+            .arg(Arg::with_name(MULTI_ARG)
+                .long("--multi")
+                .conflicts_with_all(&[CASCADE_ARG, COUNTER_ARG])
+                .help("Cipher text was encrypted for several keys or recipients. Finds the \
+                       data key wrapped for KEY_FILE (or the --identity private key). Without \
+                       it, cipher text that does not open for a single key is tried as this."))
+            .arg(Arg::with_name(PASSPHRASE_ARG)
+                .long("--passphrase")
+                .conflicts_with_all(&[KEY_FILE_ARG, CASCADE_ARG, COUNTER_ARG, IDENTITY_ARG])
+                .help("Derive the key from the passphrase used to encrypt instead of using KEY_FILE. Prompts for the passphrase."))
+            .arg(Arg::with_name(PASSPHRASE_ENV_ARG)
+                .long("--passphrase-env")
+                .value_name("VARIABLE")
+                .conflicts_with_all(&[KEY_FILE_ARG, CASCADE_ARG, COUNTER_ARG, IDENTITY_ARG])
+                .conflicts_with(PASSPHRASE_ARG)
+                .help("As --passphrase, but read the passphrase from an environment variable."))
+            .arg(Arg::with_name(PASSPHRASE_FILE_ARG)
+                .long("--passphrase-file")
+                .value_name("PASSPHRASE_FILE")
+                .conflicts_with_all(&[KEY_FILE_ARG, CASCADE_ARG, COUNTER_ARG, IDENTITY_ARG])
+                .conflicts_with_all(&[PASSPHRASE_ARG, PASSPHRASE_ENV_ARG])
+                .help("As --passphrase, but read the passphrase from the first line of a file."))
+            .arg(Arg::with_name(KEM_ARG)
+                .long("--kem")
+                .value_name("KEM_NAME")
+                .possible_values(KEM_NAMES)
+                .default_value(DEFAULT_KEM_NAME)
+                .help(&kem_help))
+            .arg(Arg::with_name(SENDER_ARG)
+                .long("--sender")
+                .value_name("PUBLIC_SIGN_KEY_FILE")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Public key of a known sender, from gensignkey. The plain text must have \
+                       been signed with encrypt --sign-key by one of them. Repeat for each \
+                       known sender. The sender that verified is reported on stderr."))
+            .arg(Arg::with_name(AAD_ARG)
+                .long("--aad")
+                .value_name("TEXT")
+                .conflicts_with(AAD_FILE_ARG)
+                .help("Associated data that is authenticated but not encrypted. Must match \
+                       the associated data given when encrypting."))
+            .arg(Arg::with_name(AAD_FILE_ARG)
+                .long("--aad-file")
+                .value_name("AAD_FILE")
+                .help("File containing associated data, read as raw bytes."))
+        )
+        .subcommand(SubCommand::with_name(GEN_SIGN_KEY_CMD)
+            .about("generate new private and public key pair for signing messages with ed25519")
+            .arg(Arg::with_name(OUTPUT_ARG)
+                .long("--output")
+                .short("o")
+                .value_name("PRIVATE_KEY_FILE")
+                .required(true)
+                .help("Output file containing generated private signing key."))
+            .arg(Arg::with_name(PUBLIC_OUTPUT_ARG)
+                .long("--public-output")
+                .short("p")
+                .value_name("PUBLIC_KEY_FILE")
+                .required(false)
+                .help("Output file containing generated public key, for verifying. If absent \
+                       output is written to stdout."))
+        )
+        .subcommand(SubCommand::with_name(SIGN_CMD)
+            .about("sign a message so that anyone with the public key can check who wrote it")
+            .arg(Arg::with_name(KEY_FILE_ARG)
+                .value_name("PRIVATE_KEY_FILE")
+                .required(true)
+                .help("File containing private signing key from gensignkey."))
+            .arg(Arg::with_name(INPUT_ARG)
+                .long("--input")
+                .short("i")
+                .value_name("PLAIN_TEXT_FILE")
+                .required(false)
+                .help("Input file containing message. If absent input is read from stdin."))
+            .arg(Arg::with_name(INPUT_FORMAT_ARG)
+                .long("--input-format")
+                .short("I")
+                .value_name("FORMAT_NAME")
+                .possible_values(ALL_FORMAT_NAMES)
+                .default_value(DEFAULT_PLAIN_FORMAT)
+                .help("Message format."))
+            .arg(Arg::with_name(OUTPUT_ARG)
+                .long("--output")
+                .short("o")
+                .value_name("SIGNED_FILE")
+                .required(false)
+                .help("Output file containing signed message, or the signature alone with \
+                       --detached. If absent output is written to stdout."))
+            .arg(Arg::with_name(OUTPUT_FORMAT_ARG)
+                .long("--output-format")
+                .short("O")
+                .value_name("FORMAT_NAME")
+                .possible_values(ALL_FORMAT_NAMES)
+                .default_value(DEFAULT_CIPHER_FORMAT)
+                .help("Signed message or signature format."))
+            .arg(Arg::with_name(DETACHED_ARG)
+                .long("--detached")
+                .short("d")
+                .help("Output the 64 byte signature alone, to be sent alongside the message."))
+        )
+        .subcommand(SubCommand::with_name(VERIFY_CMD)
+            .about("check that a message was signed by the owner of a public key")
+            .arg(Arg::with_name(KEY_FILE_ARG)
+                .value_name("PUBLIC_KEY_FILE")
+                .required(true)
+                .help("File containing public key from gensignkey."))
+            .arg(Arg::with_name(INPUT_ARG)
+                .long("--input")
+                .short("i")
+                .value_name("SIGNED_FILE")
+                .required(false)
+                .help("Input file containing signed message, or the message alone with \
+                       --signature. If absent input is read from stdin."))
+            .arg(Arg::with_name(INPUT_FORMAT_ARG)
+                .long("--input-format")
+                .short("I")
+                .value_name("FORMAT_NAME")
+                .possible_values(ALL_FORMAT_NAMES)
+                .help("Signed message format, or message format with --signature. Defaults to \
+                       signal1, or plain1 with --signature."))
+            .arg(Arg::with_name(OUTPUT_ARG)
+                .long("--output")
+                .short("o")
+                .value_name("PLAIN_TEXT_FILE")
+                .required(false)
+                .conflicts_with(SIGNATURE_ARG)
+                .help("Output file containing verified message. If absent output is written to \
+                       stdout."))
+            .arg(Arg::with_name(OUTPUT_FORMAT_ARG)
+                .long("--output-format")
+                .short("O")
+                .value_name("FORMAT_NAME")
+                .possible_values(ALL_FORMAT_NAMES)
+                .default_value(DEFAULT_PLAIN_FORMAT)
+                .help("Verified message format."))
+            .arg(Arg::with_name(SIGNATURE_ARG)
+                .long("--signature")
+                .short("s")
+                .value_name("SIGNATURE_FILE")
+                .help("File containing a signature made with sign --detached."))
+            .arg(Arg::with_name(SIGNATURE_FORMAT_ARG)
+                .long("--signature-format")
+                .value_name("FORMAT_NAME")
+                .possible_values(ALL_FORMAT_NAMES)
+                .default_value(DEFAULT_CIPHER_FORMAT)
+                .help("Signature format."))
         ).get_matches();
 
-    let algo_type = value_t!(m.value_of(ALGO_ARG), AlgoType).unwrap();
+    let algo_type = algo_type_named(&m, m.value_of(ALGO_ARG).unwrap());
     let key_format = value_t!(m.value_of(KEY_FORMAT_ARG), FormatType).unwrap();
 
     match m.subcommand() {
         (ENCRYPT_CMD, Some(c)) => {
-            let input_format = value_t!(c.value_of(INPUT_FORMAT_ARG), FormatType).unwrap();
+            let mut input_format = plain_format(c, INPUT_FORMAT_ARG, c.is_present(AGE_ARG));
             let output_format = value_t!(c.value_of(OUTPUT_FORMAT_ARG), FormatType).unwrap();
-            let mut raw_key = read_key_data(c)?;
+            let aad = read_aad(c)?;
             let mut data = read_input(c)?;
-            encrypt(
-                algo_type,
-                key_format,
-                input_format,
-                output_format,
-                &mut raw_key,
-                &mut data,
-            )?;
+            if let Some(file_name) = c.value_of(SIGN_KEY_ARG) {
+                attach_sender_signature(
+                    SignType::Ed25519,
+                    key_format.clone(),
+                    input_format,
+                    &mut read_file(file_name)?,
+                    &aad,
+                    &mut data,
+                )?;
+                input_format = FormatType::Raw;
+            }
+            if c.is_present(AGE_ARG) {
+                let passphrase = read_passphrase(c, true)?
+                    .map(|passphrase| String::from_utf8(passphrase).expect("passphrase read as text"));
+                encrypt_age(
+                    key_format,
+                    input_format,
+                    c.is_present(ARMOR_ARG),
+                    &mut read_recipient_key_data(c)?,
+                    passphrase.as_deref(),
+                    &mut data,
+                )?;
+            } else if let Some(passphrase) = read_passphrase(c, true)? {
+                encrypt_with_passphrase(
+                    algo_type,
+                    input_format,
+                    output_format,
+                    &passphrase,
+                    &read_argon2_params(c)?,
+                    &aad,
+                    &mut data,
+                )?;
+            } else if encrypt_to_many_keys(c, &algo_type) {
+                let kem_type = value_t!(c.value_of(KEM_ARG), KemType).unwrap();
+                let mut public_keys = read_x25519_recipient_key_data(&key_format, c)?;
+                encrypt_to_many(
+                    algo_type,
+                    kem_type,
+                    key_format,
+                    input_format,
+                    output_format,
+                    &mut read_all_key_data(c)?,
+                    &mut public_keys,
+                    &aad,
+                    &mut data,
+                )?;
+            } else if c.is_present(RECIPIENT_ARG) {
+                let kem_type = value_t!(c.value_of(KEM_ARG), KemType).unwrap();
+                let mut public_key = read_x25519_recipient_key_data(&key_format, c)?.remove(0);
+                encrypt_to_recipient(
+                    algo_type,
+                    kem_type,
+                    key_format,
+                    input_format,
+                    output_format,
+                    &mut public_key,
+                    &aad,
+                    &mut data,
+                )?;
+            } else if let Some(algo_types) = cascade_algo_types(&m, c) {
+                encrypt_cascade(
+                    algo_types,
+                    key_format,
+                    input_format,
+                    output_format,
+                    &mut read_all_key_data(c)?,
+                    &aad,
+                    &mut data,
+                )?;
+            } else if let AlgoType::Otp = algo_type {
+                let mut raw_key = read_key_data(c)?;
+                let (mut pad_used, pad_offset) = StateFile::open(c, PAD_USED_FILE_SUFFIX)?;
+                let next_pad_offset = encrypt_with_pad(
+                    key_format,
+                    input_format,
+                    output_format,
+                    &mut raw_key,
+                    pad_offset,
+                    &aad,
+                    &mut data,
+                )?;
+                pad_used.store(next_pad_offset)?;
+            } else if c.is_present(COUNTER_ARG) {
+                let mut raw_key = read_key_data(c)?;
+                let sender_id =
+                    value_t!(c.value_of(SENDER_ID_ARG), u8).unwrap_or_else(|e| e.exit());
+                let counter = reserve_counter(c)?;
+                encrypt_with_counter(
+                    algo_type,
+                    key_format,
+                    input_format,
+                    output_format,
+                    &mut raw_key,
+                    sender_id,
+                    counter,
+                    &aad,
+                    &mut data,
+                )?;
+            } else {
+                let mut raw_key = read_key_data(c)?;
+                encrypt(
+                    algo_type,
+                    key_format,
+                    input_format,
+                    output_format,
+                    &mut raw_key,
+                    &aad,
+                    &mut data,
+                )?;
+            }
             write_output(c, &data)
         }
 
         (DECRYPT_CMD, Some(c)) => {
             let input_format = value_t!(c.value_of(INPUT_FORMAT_ARG), FormatType).unwrap();
-            let output_format = value_t!(c.value_of(OUTPUT_FORMAT_ARG), FormatType).unwrap();
-            let mut raw_key = read_key_data(c)?;
+            let aad = read_aad(c)?;
             let mut data = read_input(c)?;
-            decrypt(
+            let age = is_age_file(&data);
+            let output_format = if c.is_present(SENDER_ARG) {
+                FormatType::Raw
+            } else {
+                plain_format(c, OUTPUT_FORMAT_ARG, age)
+            };
+            if age {
+                let passphrase = read_passphrase(c, false)?
+                    .map(|passphrase| String::from_utf8(passphrase).expect("passphrase read as text"));
+                decrypt_age(
+                    key_format.clone(),
+                    output_format,
+                    &mut read_all_key_data(c)?,
+                    passphrase.as_deref(),
+                    &mut data,
+                )?;
+            } else if let Some(passphrase) = read_passphrase(c, false)? {
+                decrypt_with_passphrase(
+                    algo_type,
+                    input_format,
+                    output_format,
+                    &passphrase,
+                    &aad,
+                    &mut data,
+                )?;
+            } else if c.is_present(IDENTITY_ARG) {
+                let kem_type = value_t!(c.value_of(KEM_ARG), KemType).unwrap();
+                let private_key = single_key(read_identity_key_data(&key_format, c)?)?;
+                decrypt_single_or_many(
+                    c,
+                    &mut data,
+                    |data| {
+                        decrypt_as_recipient(
+                            algo_type.clone(),
+                            kem_type.clone(),
+                            key_format.clone(),
+                            input_format.clone(),
+                            output_format.clone(),
+                            &mut private_key.clone(),
+                            &aad,
+                            data,
+                        )
+                    },
+                    |data| {
+                        decrypt_from_many_as_recipient(
+                            algo_type.clone(),
+                            kem_type.clone(),
+                            key_format.clone(),
+                            input_format.clone(),
+                            output_format.clone(),
+                            &mut private_key.clone(),
+                            &aad,
+                            data,
+                        )
+                    },
+                )?;
+            } else if let Some(algo_types) = cascade_algo_types(&m, c) {
+                decrypt_cascade(
+                    algo_types,
+                    key_format.clone(),
+                    input_format,
+                    output_format,
+                    &mut read_all_key_data(c)?,
+                    &aad,
+                    &mut data,
+                )?;
+            } else if c.is_present(COUNTER_ARG) {
+                let mut raw_key = read_key_data(c)?;
+                decrypt_with_counter(
+                    algo_type,
+                    key_format.clone(),
+                    input_format,
+                    output_format,
+                    &mut raw_key,
+                    &aad,
+                    &mut data,
+                )?;
+            } else {
+                let raw_key = read_key_data(c)?;
+                decrypt_single_or_many(
+                    c,
+                    &mut data,
+                    |data| {
+                        decrypt(
+                            algo_type.clone(),
+                            key_format.clone(),
+                            input_format.clone(),
+                            output_format.clone(),
+                            &mut raw_key.clone(),
+                            &aad,
+                            data,
+                        )
+                    },
+                    |data| {
+                        decrypt_from_many(
+                            algo_type.clone(),
+                            key_format.clone(),
+                            input_format.clone(),
+                            output_format.clone(),
+                            &mut raw_key.clone(),
+                            &aad,
+                            data,
+                        )
+                    },
+                )?;
+            }
+            if let Some(file_names) = c.values_of(SENDER_ARG) {
+                let file_names: Vec<&str> = file_names.collect();
+                let mut sender_keys = file_names
+                    .iter()
+                    .map(|file_name| read_file(file_name))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let sender = check_sender_signature(
+                    SignType::Ed25519,
+                    key_format,
+                    plain_format(c, OUTPUT_FORMAT_ARG, age),
+                    &mut sender_keys,
+                    &aad,
+                    &mut data,
+                )?;
+                eprintln!("Verified sender: {}", file_names[sender]);
+            }
+            write_output(c, &data)
+        }
+
+        (GEN_PAD_CMD, Some(c)) => {
+            let pad_size = value_t!(c.value_of(PAD_SIZE_ARG), usize).unwrap_or_else(|e| e.exit());
+            let raw_pad = generate_pad(pad_size, key_format)?;
+            write_output(c, &raw_pad)
+        }
+
+        (GEN_PASS_CMD, Some(c)) => {
+            let num_words = value_t!(c.value_of(NUM_WORDS_ARG), usize).unwrap_or_else(|e| e.exit());
+            let passphrase = generate_passphrase(num_words, c.value_of(SEPARATOR_ARG).unwrap());
+            eprintln!(
+                "Estimated entropy: {:.1} bits ({} words)",
+                passphrase_entropy_bits(num_words),
+                num_words
+            );
+            write_output(c, format!("{}\n", passphrase).as_bytes())
+        }
+
+        (ALGORITHMS_CMD, Some(_)) => write_stdout(&format_algorithm_table(&m)),
+
+        (GEN_KEY_PAIR_CMD, Some(c)) => {
+            let kem_type = value_t!(c.value_of(KEM_ARG), KemType).unwrap();
+            let (raw_private_key, raw_public_key) = generate_key_pair(kem_type, key_format)?;
+            write_output(c, &raw_private_key)?;
+            if let Some(file_name) = c.value_of(PUBLIC_OUTPUT_ARG) {
+                write_file(file_name, &raw_public_key)
+            } else {
+                write_stdout(&raw_public_key)
+            }
+        }
+
+        (KEY_CMD, Some(k)) => match k.subcommand() {
+            (FROM_PASSPHRASE_CMD, Some(c)) => {
+                let kdf_type = KdfType::parse(
+                    c.value_of(KDF_ARG).unwrap(),
+                    c.value_of(KDF_PARAMS_ARG).unwrap(),
+                )
+                .map_err(SigliError::from)?;
+                let salt_format = value_t!(c.value_of(SALT_FORMAT_ARG), FormatType).unwrap();
+                let passphrase = read_passphrase(c, true)?.unwrap();
+                let raw_key = derive_key_from_passphrase(
+                    algo_type,
+                    kdf_type,
+                    key_format,
+                    salt_format,
+                    &passphrase,
+                    &mut c.value_of(SALT_ARG).unwrap().as_bytes().to_vec(),
+                )?;
+                write_output(c, &raw_key)
+            }
+            (TO_AGE_CMD, Some(c)) => {
+                let age_key = if c.is_present(IDENTITY_ARG) {
+                    age_identity(key_format, &mut read_key_data(c)?)?
+                } else {
+                    age_recipient(key_format, &mut read_key_data(c)?)?
+                };
+                write_output(c, format!("{}\n", age_key).as_bytes())
+            }
+            _ => Err(CliError::NoCommand),
+        },
+
+        (DERIVE_CMD, Some(c)) => {
+            let raw_key = derive_key(
                 algo_type,
                 key_format,
-                input_format,
-                output_format,
-                &mut raw_key,
-                &mut data,
+                &mut read_key_data(c)?,
+                c.value_of(LABEL_ARG).unwrap().as_bytes(),
             )?;
+            write_output(c, &raw_key)
+        }
+
+        (GEN_SIGN_KEY_CMD, Some(c)) => {
+            let (raw_private_key, raw_public_key) =
+                generate_sign_key_pair(SignType::Ed25519, key_format)?;
+            write_output(c, &raw_private_key)?;
+            if let Some(file_name) = c.value_of(PUBLIC_OUTPUT_ARG) {
+                write_file(file_name, &raw_public_key)
+            } else {
+                write_stdout(&raw_public_key)
+            }
+        }
+
+        (SIGN_CMD, Some(c)) => {
+            let input_format = value_t!(c.value_of(INPUT_FORMAT_ARG), FormatType).unwrap();
+            let output_format = value_t!(c.value_of(OUTPUT_FORMAT_ARG), FormatType).unwrap();
+            let mut raw_key = read_key_data(c)?;
+            let mut data = read_input(c)?;
+            if c.is_present(DETACHED_ARG) {
+                data = sign_detached(
+                    SignType::Ed25519,
+                    key_format,
+                    input_format,
+                    output_format,
+                    &mut raw_key,
+                    &mut data,
+                )?;
+            } else {
+                sign(
+                    SignType::Ed25519,
+                    key_format,
+                    input_format,
+                    output_format,
+                    &mut raw_key,
+                    &mut data,
+                )?;
+            }
             write_output(c, &data)
         }
 
+        (VERIFY_CMD, Some(c)) => {
+            let mut raw_key = read_key_data(c)?;
+            let mut data = read_input(c)?;
+            if let Some(signature_file) = c.value_of(SIGNATURE_ARG) {
+                let input_format = c
+                    .value_of(INPUT_FORMAT_ARG)
+                    .unwrap_or(DEFAULT_PLAIN_FORMAT)
+                    .parse::<FormatType>()
+                    .unwrap();
+                let signature_format =
+                    value_t!(c.value_of(SIGNATURE_FORMAT_ARG), FormatType).unwrap();
+                verify_detached(
+                    SignType::Ed25519,
+                    key_format,
+                    input_format,
+                    signature_format,
+                    &mut raw_key,
+                    &mut read_file(signature_file)?,
+                    &mut data,
+                )?;
+                eprintln!("Good signature");
+                Ok(())
+            } else {
+                let input_format = c
+                    .value_of(INPUT_FORMAT_ARG)
+                    .unwrap_or(DEFAULT_CIPHER_FORMAT)
+                    .parse::<FormatType>()
+                    .unwrap();
+                let output_format = value_t!(c.value_of(OUTPUT_FORMAT_ARG), FormatType).unwrap();
+                verify(
+                    SignType::Ed25519,
+                    key_format,
+                    input_format,
+                    output_format,
+                    &mut raw_key,
+                    &mut data,
+                )?;
+                write_output(c, &data)
+            }
+        }
+
         (GEN_KEY_CMD, Some(c)) => {
-            let raw_key = generate_key(algo_type, key_format)?;
+            let raw_key = generate_key(algo_type, key_format.clone())?;
+            let key_fingerprint = fingerprint(
+                FingerprintType::Letters,
+                key_format,
+                &mut raw_key.clone(),
+            )?;
+            eprintln!("Fingerprint: {}", key_fingerprint);
             write_output(c, &raw_key)
         }
 
+        (PAKE_CMD, Some(c)) => {
+            let code = read_code(c)?;
+            let (exchange, message) = start_pake(&code, FormatType::Signal1)?;
+            eprintln!("Send this message to the other side:");
+            write_exchange_message(&FormatType::Signal1, &message)?;
+
+            eprintln!("Enter the message from the other side, then an empty line:");
+            let raw_key = finish_pake(
+                exchange,
+                algo_type,
+                key_format.clone(),
+                FormatType::Signal1,
+                &mut read_exchange_message(&FormatType::Signal1, PAKE_MESSAGE_NUM_BYTES)?,
+            )?;
+            let key_fingerprint = fingerprint(
+                FingerprintType::Letters,
+                key_format,
+                &mut raw_key.clone(),
+            )?;
+            eprintln!("Fingerprint: {}", key_fingerprint);
+            eprintln!("Check the other side has the same fingerprint. It differs if the code words did not match.");
+            write_output(c, &raw_key)
+        }
+
+        (HANDSHAKE_CMD, Some(h)) => match h.subcommand() {
+            (INITIATE_CMD, Some(c)) => {
+                let input_format = value_t!(c.value_of(INPUT_FORMAT_ARG), FormatType).unwrap();
+                let output_format = value_t!(c.value_of(OUTPUT_FORMAT_ARG), FormatType).unwrap();
+                let (handshake, message) = initiate_handshake(
+                    key_format.clone(),
+                    output_format.clone(),
+                    &mut read_key_data(c)?,
+                    &mut read_public_key(c.value_of(PEER_ARG).unwrap())?,
+                )?;
+                eprintln!("Send this message to the responder:");
+                write_exchange_message(&output_format, &message)?;
+
+                eprintln!("Enter the response, then an empty line:");
+                let mut response = read_exchange_message(&input_format, HANDSHAKE_MESSAGE_NUM_BYTES)?;
+                let raw_key = finish_handshake(
+                    handshake,
+                    algo_type,
+                    key_format,
+                    input_format,
+                    &mut response,
+                )?;
+                eprintln!("Handshake complete");
+                write_output(c, &raw_key)
+            }
+            (RESPOND_CMD, Some(c)) => {
+                let input_format = value_t!(c.value_of(INPUT_FORMAT_ARG), FormatType).unwrap();
+                let output_format = value_t!(c.value_of(OUTPUT_FORMAT_ARG), FormatType).unwrap();
+                eprintln!("Enter the message from the initiator, then an empty line:");
+                let mut message = read_exchange_message(&input_format, HANDSHAKE_MESSAGE_NUM_BYTES)?;
+                let (response, raw_key) = respond_to_handshake(
+                    algo_type,
+                    key_format,
+                    input_format,
+                    output_format.clone(),
+                    &mut read_key_data(c)?,
+                    &mut read_public_key(c.value_of(PEER_ARG).unwrap())?,
+                    &mut message,
+                )?;
+                eprintln!("Send this response to the initiator:");
+                write_exchange_message(&output_format, &response)?;
+                eprintln!("Handshake complete");
+                write_output(c, &raw_key)
+            }
+            _ => Err(CliError::NoCommand),
+        },
+
+        (FINGERPRINT_CMD, Some(c)) => {
+            let fingerprint_type = value_t!(c.value_of(STYLE_ARG), FingerprintType).unwrap();
+            let key_fingerprint = fingerprint(fingerprint_type, key_format, &mut read_key_data(c)?)?;
+            write_stdout(format!("{}\n", key_fingerprint).as_bytes())
+        }
+
         ("", None) => Err(CliError::NoCommand),
         _ => unreachable!(),
     }
@@ -0,0 +1,66 @@
+use rand::{thread_rng, Rng};
+
+/// EFF large word list: 7776 words, one for each roll of five dice.
+///
+/// Published by the Electronic Frontier Foundation under CC BY 3.0 US:
+/// https://www.eff.org/dice
+const EFF_LARGE_WORDLIST: &str = include_str!("eff_large_wordlist.txt");
+
+pub const DEFAULT_NUM_WORDS: usize = 6;
+
+pub const DEFAULT_SEPARATOR: &str = " ";
+
+/// Each line is the dice roll, a tab, then the word.
+pub(crate) fn words() -> Vec<&'static str> {
+    EFF_LARGE_WORDLIST
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .collect()
+}
+
+pub fn generate_passphrase(num_words: usize, separator: &str) -> String {
+    let words = words();
+    let mut rng = thread_rng();
+    (0..num_words)
+        .map(|_| words[rng.gen_range(0, words.len())])
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Bits of entropy in a passphrase of `num_words` words, assuming an attacker knows the
+/// word list and the number of words.
+pub fn entropy_bits(num_words: usize) -> f64 {
+    num_words as f64 * (words().len() as f64).log2()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn word_list_has_one_unique_word_for_each_roll_of_five_dice() {
+        let words = words();
+        assert_eq!(7776, words.len());
+        assert_eq!(7776, words.iter().collect::<HashSet<_>>().len());
+        assert_eq!("abacus", words[0]);
+        assert_eq!("zoom", words[7775]);
+    }
+
+    #[test]
+    fn generates_requested_number_of_words() {
+        let passphrase = generate_passphrase(4, "/");
+        assert_eq!(4, passphrase.split('/').count());
+        assert!(passphrase.split('/').all(|word| words().contains(&word)));
+    }
+
+    #[test]
+    fn generates_different_passphrases() {
+        assert_ne!(generate_passphrase(6, " "), generate_passphrase(6, " "))
+    }
+
+    #[test]
+    fn estimates_entropy_of_about_12_9_bits_per_word() {
+        assert_eq!(77.5, (entropy_bits(6) * 10.0).floor() / 10.0);
+    }
+}
@@ -0,0 +1,156 @@
+use convert_base::Convert;
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+use std::str::FromStr;
+
+use crate::diceware;
+
+pub const DEFAULT_FINGERPRINT_NAME: &str = LETTERS_NAME;
+
+pub static FINGERPRINT_NAMES: &[&str] = &[LETTERS_NAME, WORDS_NAME];
+
+const LETTERS_NAME: &str = "letters";
+
+const WORDS_NAME: &str = "words";
+
+/// Four groups of five letters, about 94 bits.
+const NUM_LETTER_GROUPS: usize = 4;
+
+/// Six words from the EFF large word list, about 77 bits.
+const NUM_WORDS: usize = 6;
+
+const FINGERPRINT_LABEL: &[u8] = b"sigli fingerprint";
+
+/// How a key fingerprint is written so that it can be read aloud.
+pub enum FingerprintType {
+    /// Groups of five capital letters, as in `signal1`.
+    ///
+    /// # Example Value
+    /// ```text
+    /// TFUQC HFVHS DSAGB DNUHL
+    /// ```
+    Letters,
+
+    /// Words from the EFF large word list used by `genpass`.
+    ///
+    /// # Example Value
+    /// ```text
+    /// monument plaster film customs rocker caring
+    /// ```
+    Words,
+}
+
+impl FromStr for FingerprintType {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            LETTERS_NAME => Ok(FingerprintType::Letters),
+            WORDS_NAME => Ok(FingerprintType::Words),
+            _ => Err("no match"),
+        }
+    }
+}
+
+fn digest(key: &[u8]) -> Vec<u8> {
+    let mut hash = Sha256::new();
+    hash.update(FINGERPRINT_LABEL);
+    hash.update(key);
+    hash.finalize().to_vec()
+}
+
+fn letters(digest: &[u8]) -> String {
+    let mut convert = Convert::new(256, 26);
+    let points = convert.convert::<u8, u8>(digest);
+    points
+        .iter()
+        .chain(std::iter::repeat(&0))
+        .take(NUM_LETTER_GROUPS * 5)
+        .map(|point| (point + b'A') as char)
+        .collect::<Vec<_>>()
+        .chunks(5)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn words(digest: &[u8]) -> String {
+    let words = diceware::words();
+    digest
+        .chunks(4)
+        .take(NUM_WORDS)
+        .map(|chunk| {
+            let index = u32::from_be_bytes(chunk.try_into().unwrap()) as usize % words.len();
+            words[index]
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fingerprint key data: a short hash that is the same for everyone holding the same key.
+pub fn fingerprint(fingerprint_type: FingerprintType, key: &[u8]) -> String {
+    let digest = digest(key);
+    match fingerprint_type {
+        FingerprintType::Letters => letters(&digest),
+        FingerprintType::Words => words(&digest),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key() -> Vec<u8> {
+        hex::decode("E1EB4267D8282ADBFF47E431ABAFFC2D").unwrap()
+    }
+
+    #[test]
+    fn matches_known_fingerprints() {
+        assert_eq!("TFUQC HFVHS DSAGB DNUHL", fingerprint(FingerprintType::Letters, &key()));
+        assert_eq!(
+            "monument plaster film customs rocker caring",
+            fingerprint(FingerprintType::Words, &key())
+        );
+    }
+
+    #[test]
+    fn letters_are_four_groups_of_five() {
+        let result = fingerprint(FingerprintType::Letters, &[0xAB, 0x01, 0x02]);
+
+        let groups: Vec<&str> = result.split(' ').collect();
+        assert_eq!(4, groups.len());
+        for group in groups {
+            assert_eq!(5, group.len());
+            assert!(group.chars().all(|c| c.is_ascii_uppercase()));
+        }
+    }
+
+    #[test]
+    fn words_are_six_words_from_word_list() {
+        let result = fingerprint(FingerprintType::Words, &[0xAB, 0x01, 0x02]);
+
+        let words = diceware::words();
+        let fingerprint_words: Vec<&str> = result.split(' ').collect();
+        assert_eq!(6, fingerprint_words.len());
+        assert!(fingerprint_words.iter().all(|word| words.contains(word)));
+    }
+
+    #[test]
+    fn same_key_gives_same_fingerprint() {
+        assert_eq!(
+            fingerprint(FingerprintType::Letters, &[0xAB, 0x01, 0x02]),
+            fingerprint(FingerprintType::Letters, &[0xAB, 0x01, 0x02])
+        );
+    }
+
+    #[test]
+    fn different_keys_give_different_fingerprints() {
+        assert_ne!(
+            fingerprint(FingerprintType::Letters, &[0xAB, 0x01, 0x02]),
+            fingerprint(FingerprintType::Letters, &[0xAB, 0x01, 0x03])
+        );
+        assert_ne!(
+            fingerprint(FingerprintType::Words, &[0xAB, 0x01, 0x02]),
+            fingerprint(FingerprintType::Words, &[0xAB, 0x01, 0x03])
+        );
+    }
+}
@@ -26,6 +26,7 @@ pub static KEY_FORMAT_NAMES: &[&str] = &[
 ];
 
 /// Format used to either unpack inputs or pack outputs.
+#[derive(Clone)]
 pub enum FormatType {
     /// Raw Binary
     ///
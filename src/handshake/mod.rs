@@ -0,0 +1,200 @@
+use snow::{Builder, HandshakeState};
+
+/// Noise KK: both sides already hold the other's static X25519 public key.
+const PATTERN: &str = "Noise_KK_25519_ChaChaPoly_SHA256";
+
+/// Prologue mixed into the handshake, so it only completes with another sigli handshake.
+const PROLOGUE: &[u8] = b"sigli handshake";
+
+const KEY_NUM_BYTES: usize = 32;
+
+/// Each message carries an ephemeral public key and the tag of an empty payload.
+pub const MESSAGE_NUM_BYTES: usize = 48;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HandshakeError {
+    PrivateKeyWrongLength {
+        expected_length: usize,
+        actual_length: usize,
+    },
+    PublicKeyWrongLength {
+        expected_length: usize,
+        actual_length: usize,
+    },
+    /// Message from the other side did not authenticate, because it is corrupt or the other
+    /// side does not hold the expected key pair.
+    HandshakeFailed,
+}
+
+/// Initiator's side of a handshake, waiting for the response.
+pub struct Handshake {
+    state: HandshakeState,
+}
+
+fn check_key_lengths(private_key: &[u8], remote_public_key: &[u8]) -> Result<(), HandshakeError> {
+    if private_key.len() != KEY_NUM_BYTES {
+        return Err(HandshakeError::PrivateKeyWrongLength {
+            expected_length: KEY_NUM_BYTES,
+            actual_length: private_key.len(),
+        });
+    }
+    if remote_public_key.len() != KEY_NUM_BYTES {
+        return Err(HandshakeError::PublicKeyWrongLength {
+            expected_length: KEY_NUM_BYTES,
+            actual_length: remote_public_key.len(),
+        });
+    }
+    Ok(())
+}
+
+fn builder<'a>(private_key: &'a [u8], remote_public_key: &'a [u8]) -> Builder<'a> {
+    Builder::new(PATTERN.parse().expect("pattern is valid"))
+        .prologue(PROLOGUE)
+        .local_private_key(private_key)
+        .remote_public_key(remote_public_key)
+}
+
+/// Both transport keys from a finished handshake, the same on both sides.
+fn split(state: &mut HandshakeState) -> Vec<u8> {
+    let (initiator_key, responder_key) = state.dangerously_get_raw_split();
+    let mut secret = initiator_key.to_vec();
+    secret.extend_from_slice(&responder_key);
+    secret
+}
+
+/// Start a handshake, returning the first message to send to the responder.
+pub fn initiate(
+    private_key: &[u8],
+    remote_public_key: &[u8],
+) -> Result<(Handshake, Vec<u8>), HandshakeError> {
+    check_key_lengths(private_key, remote_public_key)?;
+    let mut state = builder(private_key, remote_public_key)
+        .build_initiator()
+        .map_err(|_| HandshakeError::HandshakeFailed)?;
+
+    let mut message = vec![0u8; MESSAGE_NUM_BYTES];
+    state
+        .write_message(&[], &mut message)
+        .map_err(|_| HandshakeError::HandshakeFailed)?;
+
+    Ok((Handshake { state }, message))
+}
+
+/// Answer the initiator's first message, returning the message to send back and the 64 byte
+/// shared secret.
+pub fn respond(
+    private_key: &[u8],
+    remote_public_key: &[u8],
+    message: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), HandshakeError> {
+    check_key_lengths(private_key, remote_public_key)?;
+    let mut state = builder(private_key, remote_public_key)
+        .build_responder()
+        .map_err(|_| HandshakeError::HandshakeFailed)?;
+
+    let mut payload = vec![0u8; MESSAGE_NUM_BYTES];
+    state
+        .read_message(message, &mut payload)
+        .map_err(|_| HandshakeError::HandshakeFailed)?;
+
+    let mut response = vec![0u8; MESSAGE_NUM_BYTES];
+    state
+        .write_message(&[], &mut response)
+        .map_err(|_| HandshakeError::HandshakeFailed)?;
+
+    Ok((response, split(&mut state)))
+}
+
+/// Finish a handshake with the responder's message, returning the 64 byte shared secret.
+pub fn finish(mut handshake: Handshake, message: &[u8]) -> Result<Vec<u8>, HandshakeError> {
+    let mut payload = vec![0u8; MESSAGE_NUM_BYTES];
+    handshake
+        .state
+        .read_message(message, &mut payload)
+        .map_err(|_| HandshakeError::HandshakeFailed)?;
+
+    Ok(split(&mut handshake.state))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use x25519_dalek::{PublicKey, StaticSecret};
+
+    fn key_pair(seed: u8) -> (Vec<u8>, Vec<u8>) {
+        let private_key = [seed; KEY_NUM_BYTES];
+        let public_key = PublicKey::from(&StaticSecret::from(private_key));
+        (private_key.to_vec(), public_key.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn both_sides_get_same_secret() {
+        let (initiator_private, initiator_public) = key_pair(1);
+        let (responder_private, responder_public) = key_pair(2);
+
+        let (handshake, message) = initiate(&initiator_private, &responder_public).unwrap();
+        let (response, responder_secret) =
+            respond(&responder_private, &initiator_public, &message).unwrap();
+        let initiator_secret = finish(handshake, &response).unwrap();
+
+        assert_eq!(initiator_secret, responder_secret);
+        assert_eq!(64, initiator_secret.len());
+        assert_eq!(MESSAGE_NUM_BYTES, message.len());
+        assert_eq!(MESSAGE_NUM_BYTES, response.len());
+    }
+
+    #[test]
+    fn handshakes_give_fresh_secrets() {
+        let (initiator_private, initiator_public) = key_pair(1);
+        let (responder_private, responder_public) = key_pair(2);
+
+        let (_, message) = initiate(&initiator_private, &responder_public).unwrap();
+        let (_, first_secret) = respond(&responder_private, &initiator_public, &message).unwrap();
+        let (_, message) = initiate(&initiator_private, &responder_public).unwrap();
+        let (_, second_secret) = respond(&responder_private, &initiator_public, &message).unwrap();
+
+        assert_ne!(first_secret, second_secret);
+    }
+
+    #[test]
+    fn responder_rejects_unexpected_initiator() {
+        let (_, initiator_public) = key_pair(1);
+        let (responder_private, responder_public) = key_pair(2);
+        let (impostor_private, _) = key_pair(3);
+
+        let (_, message) = initiate(&impostor_private, &responder_public).unwrap();
+
+        assert_eq!(
+            HandshakeError::HandshakeFailed,
+            respond(&responder_private, &initiator_public, &message).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn initiator_rejects_unexpected_responder() {
+        let (initiator_private, initiator_public) = key_pair(1);
+        let (_, responder_public) = key_pair(2);
+        let (impostor_private, impostor_public) = key_pair(3);
+
+        let (handshake, _) = initiate(&initiator_private, &responder_public).unwrap();
+        let (_, message) = initiate(&initiator_private, &impostor_public).unwrap();
+        let (response, _) = respond(&impostor_private, &initiator_public, &message).unwrap();
+
+        assert_eq!(HandshakeError::HandshakeFailed, finish(handshake, &response).unwrap_err());
+    }
+
+    #[test]
+    fn can_not_use_wrong_length_public_key() {
+        let (initiator_private, _) = key_pair(1);
+
+        let result = initiate(&initiator_private, &[0u8; 31]).err().unwrap();
+
+        assert_eq!(
+            HandshakeError::PublicKeyWrongLength {
+                expected_length: 32,
+                actual_length: 31
+            },
+            result
+        )
+    }
+}
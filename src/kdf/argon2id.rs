@@ -0,0 +1,237 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::{thread_rng, RngCore};
+use std::convert::TryFrom;
+
+use super::KdfError;
+use crate::algo::{push_counter_prefix, take_counter_prefix};
+
+const SALT_NUM_BYTES: usize = 16;
+
+/// Largest memory cost accepted (256 MiB, about 13 times the default), so a malicious message
+/// header can not make decryption exhaust memory.
+const MAX_MEMORY_KIB: u32 = 256 * 1024;
+
+/// Largest number of iterations accepted (8 times the default), so a malicious message header
+/// can not make decryption run for hours.
+const MAX_ITERATIONS: u32 = 16;
+
+/// Largest number of lanes accepted.
+const MAX_PARALLELISM: u32 = 8;
+
+/// Cost parameters for Argon2id.
+///
+/// Higher costs make each guess at a passphrase slower, for attackers and users alike.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Argon2Params {
+    /// Memory used in KiB.
+    pub memory_kib: u32,
+    /// Number of passes over memory.
+    pub iterations: u32,
+    /// Number of lanes.
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    /// OWASP recommended minimum: 19 MiB of memory, 2 iterations and 1 lane.
+    fn default() -> Self {
+        Argon2Params {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Argon2Params {
+    fn within_limits(&self) -> bool {
+        self.memory_kib <= MAX_MEMORY_KIB
+            && self.iterations <= MAX_ITERATIONS
+            && self.parallelism <= MAX_PARALLELISM
+    }
+
+    fn derive_key(
+        &self,
+        passphrase: &[u8],
+        salt: &[u8],
+        key_num_bytes: usize,
+    ) -> Result<Vec<u8>, KdfError> {
+        if !self.within_limits() {
+            return Err(KdfError::InvalidParams);
+        }
+        let params = Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(key_num_bytes),
+        )
+        .map_err(|_| KdfError::InvalidParams)?;
+
+        let mut key = vec![0u8; key_num_bytes];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase, salt, &mut key)
+            .map_err(|_| KdfError::InvalidParams)?;
+        Ok(key)
+    }
+}
+
+/// Derive a key from the passphrase with a fresh random salt.
+///
+/// The salt and cost parameters are placed in front of `data`, so that `take_header_key`
+/// can derive the same key again from nothing but the passphrase.
+pub fn push_header_key(
+    params: &Argon2Params,
+    passphrase: &[u8],
+    key_num_bytes: usize,
+    data: &mut Vec<u8>,
+) -> Result<Vec<u8>, KdfError> {
+    let mut salt = [0u8; SALT_NUM_BYTES];
+    thread_rng().fill_bytes(&mut salt);
+    let key = params.derive_key(passphrase, &salt, key_num_bytes)?;
+
+    data.splice(0..0, salt.iter().cloned());
+    push_counter_prefix(u64::from(params.parallelism), data);
+    push_counter_prefix(u64::from(params.iterations), data);
+    push_counter_prefix(u64::from(params.memory_kib), data);
+
+    Ok(key)
+}
+
+/// Remove the header added by `push_header_key` and derive the key it describes.
+pub fn take_header_key(
+    passphrase: &[u8],
+    key_num_bytes: usize,
+    data: &mut Vec<u8>,
+) -> Result<Vec<u8>, KdfError> {
+    let mut take_param = || {
+        take_counter_prefix(data)
+            .ok()
+            .and_then(|value| u32::try_from(value).ok())
+            .ok_or(KdfError::MalformedHeader)
+    };
+    let params = Argon2Params {
+        memory_kib: take_param()?,
+        iterations: take_param()?,
+        parallelism: take_param()?,
+    };
+    if !params.within_limits() {
+        return Err(KdfError::MalformedHeader);
+    }
+
+    if data.len() < SALT_NUM_BYTES {
+        return Err(KdfError::MalformedHeader);
+    }
+    let salt: Vec<u8> = data.drain(..SALT_NUM_BYTES).collect();
+
+    params.derive_key(passphrase, &salt, key_num_bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cheap_params() -> Argon2Params {
+        Argon2Params {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn matches_reference_implementation() {
+        // Argon2id test vector from the reference implementation (phc-winner-argon2 test.c).
+        let key = Argon2Params {
+            memory_kib: 1 << 16,
+            iterations: 2,
+            parallelism: 1,
+        }
+        .derive_key(b"password", b"somesalt", 32)
+        .unwrap();
+
+        assert_eq!(
+            "09316115d5cf24ed5a15a31a3ba326e5cf32edc24702987c02b6566f61913cf7",
+            hex::encode(key)
+        );
+    }
+
+    #[test]
+    fn can_round_trip_header() {
+        let mut data = vec![0xAB, 0x01];
+
+        let key1 = push_header_key(&cheap_params(), b"passphrase", 32, &mut data).unwrap();
+        assert_eq!(3 + 16 + 2, data.len());
+        let key2 = take_header_key(b"passphrase", 32, &mut data).unwrap();
+
+        assert_eq!(key1, key2);
+        assert_eq!(vec![0xAB, 0x01], data);
+    }
+
+    #[test]
+    fn uses_different_salt_each_time() {
+        let mut data1 = Vec::new();
+        let mut data2 = Vec::new();
+
+        let key1 = push_header_key(&cheap_params(), b"passphrase", 32, &mut data1).unwrap();
+        let key2 = push_header_key(&cheap_params(), b"passphrase", 32, &mut data2).unwrap();
+
+        assert_ne!(key1, key2);
+        assert_ne!(data1, data2);
+    }
+
+    #[test]
+    fn different_passphrase_gives_different_key() {
+        let mut data = Vec::new();
+
+        let key1 = push_header_key(&cheap_params(), b"passphrase", 32, &mut data).unwrap();
+        let key2 = take_header_key(b"passphrasf", 32, &mut data).unwrap();
+
+        assert_ne!(key1, key2);
+    }
+
+    #[test]
+    fn can_not_take_truncated_header() {
+        let mut data = Vec::new();
+        push_header_key(&cheap_params(), b"passphrase", 32, &mut data).unwrap();
+        data.truncate(10);
+
+        let result = take_header_key(b"passphrase", 32, &mut data).unwrap_err();
+        assert_eq!(KdfError::MalformedHeader, result);
+    }
+
+    fn header(memory_kib: u32, iterations: u32, parallelism: u32) -> Vec<u8> {
+        let mut data = vec![0u8; SALT_NUM_BYTES];
+        push_counter_prefix(u64::from(parallelism), &mut data);
+        push_counter_prefix(u64::from(iterations), &mut data);
+        push_counter_prefix(u64::from(memory_kib), &mut data);
+        data
+    }
+
+    #[test]
+    fn can_not_use_excessive_costs_from_header() {
+        for (memory_kib, iterations, parallelism) in [
+            (u32::MAX, 1, 1),
+            (64, u32::MAX, 1),
+            (64, 1, u32::MAX),
+            (u32::MAX, u32::MAX, 1),
+            (MAX_MEMORY_KIB + 1, 1, 1),
+            (64, MAX_ITERATIONS + 1, 1),
+        ] {
+            let mut data = header(memory_kib, iterations, parallelism);
+
+            let result = take_header_key(b"passphrase", 32, &mut data).unwrap_err();
+            assert_eq!(KdfError::MalformedHeader, result);
+        }
+    }
+
+    #[test]
+    fn can_not_encrypt_with_excessive_costs() {
+        let params = Argon2Params {
+            memory_kib: 64,
+            iterations: MAX_ITERATIONS + 1,
+            parallelism: 1,
+        };
+
+        let result = push_header_key(&params, b"passphrase", 32, &mut Vec::new()).unwrap_err();
+        assert_eq!(KdfError::InvalidParams, result);
+    }
+}
@@ -0,0 +1,66 @@
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+use super::KdfError;
+
+/// Longest output HKDF-SHA256 can produce.
+const MAX_KEY_NUM_BYTES: usize = 255 * 32;
+
+/// Derive a sub key from a master key with HKDF-SHA256.
+///
+/// No salt is used and the label is used as the HKDF info, so any HKDF-SHA256
+/// implementation can reproduce the key.
+pub fn derive_sub_key(
+    master_key: &[u8],
+    label: &[u8],
+    key_num_bytes: usize,
+) -> Result<Vec<u8>, KdfError> {
+    if key_num_bytes > MAX_KEY_NUM_BYTES {
+        return Err(KdfError::InvalidParams);
+    }
+    let mut key = vec![0u8; key_num_bytes];
+    Hkdf::<Sha256>::new(None, master_key)
+        .expand(label, &mut key)
+        .map_err(|_| KdfError::InvalidParams)?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_rfc_5869_test_case_3() {
+        let key = derive_sub_key(&[0x0B; 22], &[], 42).unwrap();
+
+        assert_eq!(
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d\
+             9d201395faa4b61a96c8",
+            hex::encode(key)
+        );
+    }
+
+    #[test]
+    fn different_labels_give_different_keys() {
+        let master_key = [0x42; 32];
+        assert_ne!(
+            derive_sub_key(&master_key, b"contact alice", 32).unwrap(),
+            derive_sub_key(&master_key, b"contact bob", 32).unwrap()
+        );
+    }
+
+    #[test]
+    fn same_label_gives_same_key() {
+        let master_key = [0x42; 32];
+        assert_eq!(
+            derive_sub_key(&master_key, b"contact alice", 32).unwrap(),
+            derive_sub_key(&master_key, b"contact alice", 32).unwrap()
+        );
+    }
+
+    #[test]
+    fn can_not_derive_overlong_key() {
+        let result = derive_sub_key(&[0x42; 32], b"label", 255 * 32 + 1).unwrap_err();
+        assert_eq!(KdfError::InvalidParams, result);
+    }
+}
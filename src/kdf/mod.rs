@@ -0,0 +1,110 @@
+use std::str::FromStr;
+
+mod argon2id;
+mod hkdf;
+mod pbkdf2;
+mod scrypt;
+
+pub use self::argon2id::Argon2Params;
+
+pub(crate) use self::argon2id::{push_header_key, take_header_key};
+pub(crate) use self::hkdf::derive_sub_key;
+
+pub static KDF_NAMES: &[&str] = &[pbkdf2::KDF_NAME, scrypt::KDF_NAME];
+
+/// Function used to derive a key file from a passphrase and salt.
+///
+/// Both sides of a conversation that use the same function, parameters, passphrase and salt
+/// arrive at the same key.
+pub enum KdfType {
+
+    /// PBKDF2 with HMAC-SHA256 (RFC 8018).
+    ///
+    /// Implemented using [RustCrypto/password-hashes]: https://github.com/RustCrypto/password-hashes
+    ///
+    /// Widely supported, but cheap to attack with dedicated hardware, so use a high
+    /// iteration count (OWASP recommends 600,000).
+    Pbkdf2 { iterations: u32 },
+
+    /// scrypt (RFC 7914).
+    ///
+    /// Implemented using [RustCrypto/password-hashes]: https://github.com/RustCrypto/password-hashes
+    ///
+    /// Memory hard. Uses `128 * r * 2^log_n` bytes of memory (16 MiB with `log_n` 14 and
+    /// `r` 8).
+    Scrypt { log_n: u8, r: u32, p: u32 },
+}
+
+impl KdfType {
+    /// Select a key derivation function by name, with its parameters separated by commas.
+    ///
+    /// `pbkdf2` takes `ITERATIONS` and `scrypt` takes `LOG_N,R,P`.
+    pub fn parse(name: &str, params: &str) -> Result<Self, KdfError> {
+        let values = params
+            .split(',')
+            .map(|value| u32::from_str(value.trim()).map_err(|_| KdfError::InvalidParams))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match (name, values.as_slice()) {
+            (pbkdf2::KDF_NAME, [iterations]) => Ok(KdfType::Pbkdf2 {
+                iterations: *iterations,
+            }),
+            (scrypt::KDF_NAME, [log_n, r, p]) if *log_n <= u32::from(u8::MAX) => {
+                Ok(KdfType::Scrypt {
+                    log_n: *log_n as u8,
+                    r: *r,
+                    p: *p,
+                })
+            }
+            _ => Err(KdfError::InvalidParams),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum KdfError {
+    InvalidParams,
+    KeyLengthNotFixed,
+    MalformedHeader,
+}
+
+pub trait Kdf {
+    fn derive_key(
+        &self,
+        passphrase: &[u8],
+        salt: &[u8],
+        key_num_bytes: usize,
+    ) -> Result<Vec<u8>, KdfError>;
+}
+
+pub fn select_kdf(name: KdfType) -> Box<dyn Kdf> {
+    match name {
+        KdfType::Pbkdf2 { iterations } => Box::new(pbkdf2::Pbkdf2Kdf { iterations }),
+        KdfType::Scrypt { log_n, r, p } => Box::new(scrypt::ScryptKdf { log_n, r, p }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn can_parse_params() {
+        match KdfType::parse("pbkdf2", "600000").unwrap() {
+            KdfType::Pbkdf2 { iterations } => assert_eq!(600000, iterations),
+            _ => panic!("wrong kdf"),
+        }
+        match KdfType::parse("scrypt", "14, 8, 1").unwrap() {
+            KdfType::Scrypt { log_n, r, p } => assert_eq!((14, 8, 1), (log_n, r, p)),
+            _ => panic!("wrong kdf"),
+        }
+    }
+
+    #[test]
+    fn can_not_parse_wrong_number_of_params() {
+        assert!(KdfType::parse("pbkdf2", "1,2").is_err());
+        assert!(KdfType::parse("scrypt", "14,8").is_err());
+        assert!(KdfType::parse("scrypt", "256,8,1").is_err());
+        assert!(KdfType::parse("bcrypt", "10").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+
+use super::{Kdf, KdfError};
+
+pub struct Pbkdf2Kdf {
+    pub iterations: u32,
+}
+
+pub const KDF_NAME: &str = "pbkdf2";
+
+impl Kdf for Pbkdf2Kdf {
+    fn derive_key(
+        &self,
+        passphrase: &[u8],
+        salt: &[u8],
+        key_num_bytes: usize,
+    ) -> Result<Vec<u8>, KdfError> {
+        if self.iterations == 0 {
+            return Err(KdfError::InvalidParams);
+        }
+        let mut key = vec![0u8; key_num_bytes];
+        pbkdf2_hmac::<Sha256>(passphrase, salt, self.iterations, &mut key);
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn derive_hex(passphrase: &[u8], salt: &[u8], iterations: u32, key_num_bytes: usize) -> String {
+        let key = Pbkdf2Kdf { iterations }
+            .derive_key(passphrase, salt, key_num_bytes)
+            .unwrap();
+        hex::encode(key)
+    }
+
+    #[test]
+    fn matches_rfc_7914_test_vectors() {
+        assert_eq!(
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783",
+            derive_hex(b"passwd", b"salt", 1, 64)
+        );
+        assert_eq!(
+            "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56\
+             a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d",
+            derive_hex(b"Password", b"NaCl", 80000, 64)
+        );
+    }
+
+    #[test]
+    fn matches_published_sha256_test_vectors() {
+        assert_eq!(
+            "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b",
+            derive_hex(b"password", b"salt", 1, 32)
+        );
+        assert_eq!(
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a",
+            derive_hex(b"password", b"salt", 4096, 32)
+        );
+    }
+
+    #[test]
+    fn can_not_use_zero_iterations() {
+        let result = Pbkdf2Kdf { iterations: 0 }
+            .derive_key(b"password", b"salt", 32)
+            .unwrap_err();
+        assert_eq!(KdfError::InvalidParams, result);
+    }
+}
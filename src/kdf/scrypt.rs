@@ -0,0 +1,60 @@
+use scrypt::{scrypt, Params};
+
+use super::{Kdf, KdfError};
+
+pub struct ScryptKdf {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+pub const KDF_NAME: &str = "scrypt";
+
+impl Kdf for ScryptKdf {
+    fn derive_key(
+        &self,
+        passphrase: &[u8],
+        salt: &[u8],
+        key_num_bytes: usize,
+    ) -> Result<Vec<u8>, KdfError> {
+        let params = Params::new(self.log_n, self.r, self.p, key_num_bytes)
+            .map_err(|_| KdfError::InvalidParams)?;
+        let mut key = vec![0u8; key_num_bytes];
+        scrypt(passphrase, salt, &params, &mut key).map_err(|_| KdfError::InvalidParams)?;
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn derive_hex(passphrase: &[u8], salt: &[u8], log_n: u8, r: u32, p: u32) -> String {
+        let key = ScryptKdf { log_n, r, p }
+            .derive_key(passphrase, salt, 64)
+            .unwrap();
+        hex::encode(key)
+    }
+
+    #[test]
+    fn matches_rfc_7914_test_vectors() {
+        assert_eq!(
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+             fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906",
+            derive_hex(b"", b"", 4, 1, 1)
+        );
+        assert_eq!(
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+             2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640",
+            derive_hex(b"password", b"NaCl", 10, 8, 16)
+        );
+    }
+
+    #[test]
+    fn can_not_use_invalid_params() {
+        let result = ScryptKdf { log_n: 4, r: 0, p: 1 }
+            .derive_key(b"password", b"salt", 32)
+            .unwrap_err();
+        assert_eq!(KdfError::InvalidParams, result);
+    }
+}
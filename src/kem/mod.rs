@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+mod x25519;
+mod x25519mlkem768;
+
+pub const DEFAULT_KEM_NAME: &str = x25519::KEM_NAME;
+
+pub static KEM_NAMES: &[&str] = &[x25519::KEM_NAME, x25519mlkem768::KEM_NAME];
+
+/// Key encapsulation mechanism used to send to a recipient's public key.
+#[derive(Clone)]
+pub enum KemType {
+
+    /// X25519 (RFC 7748) Diffie-Hellman with an ephemeral sender key, as in a sealed box.
+    ///
+    /// Implemented using [dalek-cryptography]: https://github.com/dalek-cryptography/curve25519-dalek
+    ///
+    /// The sender is anonymous: nothing in the message identifies who encrypted it. Keys are
+    /// short enough to read aloud.
+    ///
+    /// - 32 byte private key.
+    /// - 32 byte public key.
+    /// - 32 byte encapsulated key (the ephemeral public key) added to each message.
+    X25519,
+
+    /// Hybrid of X25519 and ML-KEM-768 (FIPS 203).
+    ///
+    /// Implemented using [RustCrypto/KEMs]: https://github.com/RustCrypto/KEMs and
+    /// [dalek-cryptography]: https://github.com/dalek-cryptography/curve25519-dalek
+    ///
+    /// Both shared secrets are hashed together with SHA3-256, so a message stays secret
+    /// unless both X25519 and ML-KEM-768 are broken. This protects messages that must stay
+    /// confidential after large quantum computers exist.
+    ///
+    /// - 32 byte private key (a seed from which both private keys are derived).
+    /// - 1216 byte public key.
+    /// - 1120 byte encapsulated key added to each message.
+    X25519MlKem768,
+}
+
+impl FromStr for KemType {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            x25519::KEM_NAME => Ok(KemType::X25519),
+            x25519mlkem768::KEM_NAME => Ok(KemType::X25519MlKem768),
+            _ => Err("no match"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum KemError {
+    PrivateKeyWrongLength {
+        expected_length: usize,
+        actual_length: usize,
+    },
+    PublicKeyWrongLength {
+        expected_length: usize,
+        actual_length: usize,
+    },
+    EncapsulatedKeyTooShort,
+    /// Public key is a low order point that would give a predictable shared secret.
+    InvalidPublicKey,
+}
+
+/// Sizes of the keys used by a key encapsulation mechanism.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct KemInfo {
+    pub private_key_num_bytes: usize,
+    pub public_key_num_bytes: usize,
+    /// Bytes added to each message to carry the encapsulated key.
+    pub encapsulated_key_num_bytes: usize,
+}
+
+pub trait Kem {
+    fn info(&self) -> KemInfo;
+
+    /// Generate a new key pair, returned as private key data then public key data.
+    fn generate_key_pair_data(&self) -> (Vec<u8>, Vec<u8>);
+
+    /// Create a fresh shared secret for the owner of `public_key`.
+    ///
+    /// Returns the encapsulated key to send to the recipient and the 32 byte shared secret.
+    fn encapsulate(&self, public_key: &[u8]) -> Result<(Vec<u8>, Vec<u8>), KemError>;
+
+    /// Recover the 32 byte shared secret from the encapsulated key at the start of `data`,
+    /// removing the encapsulated key from `data`.
+    fn decapsulate(&self, private_key: &[u8], data: &mut Vec<u8>) -> Result<Vec<u8>, KemError>;
+}
+
+pub fn select_kem(name: KemType) -> Box<dyn Kem> {
+    match name {
+        KemType::X25519 => Box::new(x25519::X25519Kem {}),
+        KemType::X25519MlKem768 => Box::new(x25519mlkem768::X25519MlKem768Kem {}),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reported_sizes_match_generated_keys() {
+        for name in KEM_NAMES {
+            let kem = select_kem(KemType::from_str(name).unwrap());
+            let (private_key, public_key) = kem.generate_key_pair_data();
+            let (encapsulated_key, shared_secret) = kem.encapsulate(&public_key).unwrap();
+
+            assert_eq!(kem.info().private_key_num_bytes, private_key.len(), "{}", name);
+            assert_eq!(kem.info().public_key_num_bytes, public_key.len(), "{}", name);
+            assert_eq!(kem.info().encapsulated_key_num_bytes, encapsulated_key.len(), "{}", name);
+            assert_eq!(32, shared_secret.len(), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use rand::{thread_rng, RngCore};
+use sha3::{Digest, Sha3_256};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use super::{Kem, KemError, KemInfo};
+
+pub struct X25519Kem {}
+
+pub const KEM_NAME: &str = "x25519";
+
+const X25519_NUM_BYTES: usize = 32;
+
+const COMBINER_LABEL: &[u8] = b"sigli x25519";
+
+impl X25519Kem {
+    fn public_key_from(data: &[u8]) -> Result<PublicKey, KemError> {
+        if data.len() != X25519_NUM_BYTES {
+            return Err(KemError::PublicKeyWrongLength {
+                expected_length: X25519_NUM_BYTES,
+                actual_length: data.len(),
+            });
+        };
+        let mut public_key = [0u8; X25519_NUM_BYTES];
+        public_key.copy_from_slice(data);
+        Ok(PublicKey::from(public_key))
+    }
+
+    fn private_key_from(data: &[u8]) -> Result<StaticSecret, KemError> {
+        if data.len() != X25519_NUM_BYTES {
+            return Err(KemError::PrivateKeyWrongLength {
+                expected_length: X25519_NUM_BYTES,
+                actual_length: data.len(),
+            });
+        };
+        let mut private_key = [0u8; X25519_NUM_BYTES];
+        private_key.copy_from_slice(data);
+        Ok(StaticSecret::from(private_key))
+    }
+
+    /// Hash the Diffie-Hellman output together with both public keys.
+    fn combine(
+        secret: &[u8],
+        ephemeral_public_key: &PublicKey,
+        recipient_public_key: &PublicKey,
+    ) -> Vec<u8> {
+        let mut hash = Sha3_256::new();
+        hash.update(secret);
+        hash.update(ephemeral_public_key.as_bytes());
+        hash.update(recipient_public_key.as_bytes());
+        hash.update(COMBINER_LABEL);
+        hash.finalize().to_vec()
+    }
+}
+
+impl Kem for X25519Kem {
+    fn info(&self) -> KemInfo {
+        KemInfo {
+            private_key_num_bytes: X25519_NUM_BYTES,
+            public_key_num_bytes: X25519_NUM_BYTES,
+            encapsulated_key_num_bytes: X25519_NUM_BYTES,
+        }
+    }
+
+    fn generate_key_pair_data(&self) -> (Vec<u8>, Vec<u8>) {
+        let mut private_key = [0u8; X25519_NUM_BYTES];
+        thread_rng().fill_bytes(&mut private_key);
+        let public_key = PublicKey::from(&StaticSecret::from(private_key));
+        (private_key.to_vec(), public_key.as_bytes().to_vec())
+    }
+
+    fn encapsulate(&self, public_key: &[u8]) -> Result<(Vec<u8>, Vec<u8>), KemError> {
+        let recipient_public_key = Self::public_key_from(public_key)?;
+
+        let mut ephemeral_data = [0u8; X25519_NUM_BYTES];
+        thread_rng().fill_bytes(&mut ephemeral_data);
+        let ephemeral = StaticSecret::from(ephemeral_data);
+        let ephemeral_public_key = PublicKey::from(&ephemeral);
+
+        let secret = ephemeral.diffie_hellman(&recipient_public_key);
+        if !secret.was_contributory() {
+            return Err(KemError::InvalidPublicKey);
+        }
+
+        let shared_secret =
+            Self::combine(secret.as_bytes(), &ephemeral_public_key, &recipient_public_key);
+
+        Ok((ephemeral_public_key.as_bytes().to_vec(), shared_secret))
+    }
+
+    fn decapsulate(&self, private_key: &[u8], data: &mut Vec<u8>) -> Result<Vec<u8>, KemError> {
+        let private_key = Self::private_key_from(private_key)?;
+        if data.len() < X25519_NUM_BYTES {
+            return Err(KemError::EncapsulatedKeyTooShort);
+        }
+
+        let message = data.split_off(X25519_NUM_BYTES);
+        let encapsulated_key = std::mem::replace(data, message);
+        let ephemeral_public_key = Self::public_key_from(&encapsulated_key)?;
+
+        let secret = private_key.diffie_hellman(&ephemeral_public_key);
+        if !secret.was_contributory() {
+            return Err(KemError::InvalidPublicKey);
+        }
+
+        Ok(Self::combine(
+            secret.as_bytes(),
+            &ephemeral_public_key,
+            &PublicKey::from(&private_key),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw_data() -> Vec<u8> {
+        vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43]
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let kem = X25519Kem {};
+        let (private_key, public_key) = kem.generate_key_pair_data();
+
+        let (mut data, sent_secret) = kem.encapsulate(&public_key).unwrap();
+        data.extend_from_slice(&raw_data());
+
+        let received_secret = kem.decapsulate(&private_key, &mut data).unwrap();
+        assert_eq!(sent_secret, received_secret);
+        assert_eq!(raw_data(), data);
+    }
+
+    #[test]
+    fn public_key_matches_rfc_7748_test_vector() {
+        let private_key =
+            hex::decode("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a")
+                .unwrap();
+        let public_key = PublicKey::from(&X25519Kem::private_key_from(&private_key).unwrap());
+        assert_eq!(
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a",
+            hex::encode(public_key.as_bytes())
+        );
+    }
+
+    #[test]
+    fn encapsulating_twice_generates_different_secrets() {
+        let kem = X25519Kem {};
+        let (_, public_key) = kem.generate_key_pair_data();
+        assert_ne!(kem.encapsulate(&public_key).unwrap(), kem.encapsulate(&public_key).unwrap())
+    }
+
+    #[test]
+    fn wrong_private_key_gives_different_secret() {
+        let kem = X25519Kem {};
+        let (_, public_key) = kem.generate_key_pair_data();
+        let (other_private_key, _) = kem.generate_key_pair_data();
+
+        let (mut data, sent_secret) = kem.encapsulate(&public_key).unwrap();
+
+        let received_secret = kem.decapsulate(&other_private_key, &mut data).unwrap();
+        assert_ne!(sent_secret, received_secret);
+    }
+
+    #[test]
+    fn can_not_encapsulate_for_low_order_public_key() {
+        let kem = X25519Kem {};
+
+        let result = kem.encapsulate(&[0u8; 32]).unwrap_err();
+
+        assert_eq!(KemError::InvalidPublicKey, result);
+    }
+
+    #[test]
+    fn can_not_decapsulate_low_order_encapsulated_key() {
+        let kem = X25519Kem {};
+        let (private_key, _) = kem.generate_key_pair_data();
+        let mut data = vec![0u8; 40];
+
+        let result = kem.decapsulate(&private_key, &mut data).unwrap_err();
+
+        assert_eq!(KemError::InvalidPublicKey, result);
+    }
+
+    #[test]
+    fn can_not_decapsulate_truncated_key() {
+        let kem = X25519Kem {};
+        let (private_key, _) = kem.generate_key_pair_data();
+        let mut data = vec![0u8; 31];
+
+        let result = kem.decapsulate(&private_key, &mut data).unwrap_err();
+        assert_eq!(KemError::EncapsulatedKeyTooShort, result);
+    }
+
+    #[test]
+    fn can_not_use_wrong_length_public_key() {
+        let kem = X25519Kem {};
+
+        let result = kem.encapsulate(&[0u8; 1216]).unwrap_err();
+
+        assert_eq!(
+            KemError::PublicKeyWrongLength {
+                expected_length: 32,
+                actual_length: 1216
+            },
+            result
+        )
+    }
+}
@@ -0,0 +1,280 @@
+use ml_kem::kem::{Decapsulate, DecapsulationKey, EncapsulationKey};
+use ml_kem::{EncapsulateDeterministic, EncodedSizeUser, KemCore, MlKem768, MlKem768Params, B32};
+use rand::{thread_rng, RngCore};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Digest, Sha3_256, Shake256};
+use std::convert::TryInto;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use super::{Kem, KemError, KemInfo};
+
+pub struct X25519MlKem768Kem {}
+
+pub const KEM_NAME: &str = "x25519mlkem768";
+
+const SEED_NUM_BYTES: usize = 32;
+
+const X25519_NUM_BYTES: usize = 32;
+
+const ML_KEM_PUBLIC_KEY_NUM_BYTES: usize = 1184;
+
+const ML_KEM_CIPHER_TEXT_NUM_BYTES: usize = 1088;
+
+const COMBINER_LABEL: &[u8] = b"sigli x25519mlkem768";
+
+struct PrivateKey {
+    ml_kem: DecapsulationKey<MlKem768Params>,
+    x25519: StaticSecret,
+}
+
+impl X25519MlKem768Kem {
+    /// Expand the private key seed into the ML-KEM and X25519 private keys.
+    fn expand_seed(seed: &[u8]) -> Result<(PrivateKey, Vec<u8>), KemError> {
+        if seed.len() != SEED_NUM_BYTES {
+            return Err(KemError::PrivateKeyWrongLength {
+                expected_length: SEED_NUM_BYTES,
+                actual_length: seed.len(),
+            });
+        };
+
+        let mut shake = Shake256::default();
+        shake.update(seed);
+        let mut reader = shake.finalize_xof();
+        let mut d = B32::default();
+        let mut z = B32::default();
+        let mut x25519_data = [0u8; X25519_NUM_BYTES];
+        reader.read(&mut d);
+        reader.read(&mut z);
+        reader.read(&mut x25519_data);
+
+        let (ml_kem, ml_kem_public) = MlKem768::generate_deterministic(&d, &z);
+        let x25519 = StaticSecret::from(x25519_data);
+
+        let mut public_key = ml_kem_public.as_bytes().to_vec();
+        public_key.extend_from_slice(PublicKey::from(&x25519).as_bytes());
+
+        Ok((PrivateKey { ml_kem, x25519 }, public_key))
+    }
+
+    /// Hash both shared secrets together, bound to the X25519 values they came from.
+    fn combine(
+        ml_kem_secret: &[u8],
+        x25519_secret: &[u8],
+        x25519_cipher_text: &[u8],
+        x25519_public_key: &[u8],
+    ) -> Vec<u8> {
+        let mut hash = Sha3_256::new();
+        Digest::update(&mut hash, ml_kem_secret);
+        Digest::update(&mut hash, x25519_secret);
+        Digest::update(&mut hash, x25519_cipher_text);
+        Digest::update(&mut hash, x25519_public_key);
+        Digest::update(&mut hash, COMBINER_LABEL);
+        hash.finalize().to_vec()
+    }
+}
+
+impl Kem for X25519MlKem768Kem {
+    fn info(&self) -> KemInfo {
+        KemInfo {
+            private_key_num_bytes: SEED_NUM_BYTES,
+            public_key_num_bytes: ML_KEM_PUBLIC_KEY_NUM_BYTES + X25519_NUM_BYTES,
+            encapsulated_key_num_bytes: ML_KEM_CIPHER_TEXT_NUM_BYTES + X25519_NUM_BYTES,
+        }
+    }
+
+    fn generate_key_pair_data(&self) -> (Vec<u8>, Vec<u8>) {
+        let mut seed = vec![0u8; SEED_NUM_BYTES];
+        thread_rng().fill_bytes(&mut seed);
+        let (_, public_key) = Self::expand_seed(&seed).expect("seed has correct length");
+        (seed, public_key)
+    }
+
+    fn encapsulate(&self, public_key: &[u8]) -> Result<(Vec<u8>, Vec<u8>), KemError> {
+        if public_key.len() != ML_KEM_PUBLIC_KEY_NUM_BYTES + X25519_NUM_BYTES {
+            return Err(KemError::PublicKeyWrongLength {
+                expected_length: ML_KEM_PUBLIC_KEY_NUM_BYTES + X25519_NUM_BYTES,
+                actual_length: public_key.len(),
+            });
+        };
+        let (ml_kem_public_data, x25519_public_data) =
+            public_key.split_at(ML_KEM_PUBLIC_KEY_NUM_BYTES);
+
+        let ml_kem_public =
+            EncapsulationKey::<MlKem768Params>::from_bytes(ml_kem_public_data.try_into().unwrap());
+        let mut m = B32::default();
+        thread_rng().fill_bytes(&mut m);
+        let (ml_kem_cipher_text, ml_kem_secret) = ml_kem_public
+            .encapsulate_deterministic(&m)
+            .expect("ML-KEM encapsulation is infallible");
+
+        let mut ephemeral_data = [0u8; X25519_NUM_BYTES];
+        thread_rng().fill_bytes(&mut ephemeral_data);
+        let ephemeral = StaticSecret::from(ephemeral_data);
+        let x25519_cipher_text = PublicKey::from(&ephemeral);
+        let mut x25519_public = [0u8; X25519_NUM_BYTES];
+        x25519_public.copy_from_slice(x25519_public_data);
+        let x25519_secret = ephemeral.diffie_hellman(&PublicKey::from(x25519_public));
+        if !x25519_secret.was_contributory() {
+            return Err(KemError::InvalidPublicKey);
+        }
+
+        let shared_secret = Self::combine(
+            &ml_kem_secret,
+            x25519_secret.as_bytes(),
+            x25519_cipher_text.as_bytes(),
+            x25519_public_data,
+        );
+
+        let mut encapsulated_key = ml_kem_cipher_text.to_vec();
+        encapsulated_key.extend_from_slice(x25519_cipher_text.as_bytes());
+
+        Ok((encapsulated_key, shared_secret))
+    }
+
+    fn decapsulate(&self, private_key: &[u8], data: &mut Vec<u8>) -> Result<Vec<u8>, KemError> {
+        let (private_key, public_key) = Self::expand_seed(private_key)?;
+        if data.len() < ML_KEM_CIPHER_TEXT_NUM_BYTES + X25519_NUM_BYTES {
+            return Err(KemError::EncapsulatedKeyTooShort);
+        }
+
+        let message = data.split_off(ML_KEM_CIPHER_TEXT_NUM_BYTES + X25519_NUM_BYTES);
+        let encapsulated_key = std::mem::replace(data, message);
+        let (ml_kem_cipher_text, x25519_cipher_text) =
+            encapsulated_key.split_at(ML_KEM_CIPHER_TEXT_NUM_BYTES);
+
+        // ML-KEM never fails here: a bad cipher text gives an unrelated secret instead.
+        let ml_kem_secret = private_key
+            .ml_kem
+            .decapsulate(ml_kem_cipher_text.try_into().unwrap())
+            .expect("ML-KEM decapsulation is infallible");
+
+        let mut x25519_cipher_data = [0u8; X25519_NUM_BYTES];
+        x25519_cipher_data.copy_from_slice(x25519_cipher_text);
+        let x25519_secret = private_key
+            .x25519
+            .diffie_hellman(&PublicKey::from(x25519_cipher_data));
+        if !x25519_secret.was_contributory() {
+            return Err(KemError::InvalidPublicKey);
+        }
+
+        Ok(Self::combine(
+            &ml_kem_secret,
+            x25519_secret.as_bytes(),
+            x25519_cipher_text,
+            &public_key[ML_KEM_PUBLIC_KEY_NUM_BYTES..],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw_data() -> Vec<u8> {
+        vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43]
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let kem = X25519MlKem768Kem {};
+        let (private_key, public_key) = kem.generate_key_pair_data();
+
+        let (mut data, sent_secret) = kem.encapsulate(&public_key).unwrap();
+        data.extend_from_slice(&raw_data());
+
+        let received_secret = kem.decapsulate(&private_key, &mut data).unwrap();
+        assert_eq!(sent_secret, received_secret);
+        assert_eq!(raw_data(), data);
+    }
+
+    #[test]
+    fn generates_different_key_pairs() {
+        let kem = X25519MlKem768Kem {};
+        assert_ne!(kem.generate_key_pair_data(), kem.generate_key_pair_data())
+    }
+
+    #[test]
+    fn encapsulating_twice_generates_different_secrets() {
+        let kem = X25519MlKem768Kem {};
+        let (_, public_key) = kem.generate_key_pair_data();
+        assert_ne!(kem.encapsulate(&public_key).unwrap(), kem.encapsulate(&public_key).unwrap())
+    }
+
+    #[test]
+    fn wrong_private_key_gives_different_secret() {
+        let kem = X25519MlKem768Kem {};
+        let (_, public_key) = kem.generate_key_pair_data();
+        let (other_private_key, _) = kem.generate_key_pair_data();
+
+        let (mut data, sent_secret) = kem.encapsulate(&public_key).unwrap();
+
+        let received_secret = kem.decapsulate(&other_private_key, &mut data).unwrap();
+        assert_ne!(sent_secret, received_secret);
+    }
+
+    #[test]
+    fn can_not_encapsulate_for_low_order_x25519_public_key() {
+        let kem = X25519MlKem768Kem {};
+        let (_, mut public_key) = kem.generate_key_pair_data();
+        public_key.truncate(ML_KEM_PUBLIC_KEY_NUM_BYTES);
+        public_key.extend_from_slice(&[0u8; X25519_NUM_BYTES]);
+
+        let result = kem.encapsulate(&public_key).unwrap_err();
+
+        assert_eq!(KemError::InvalidPublicKey, result);
+    }
+
+    #[test]
+    fn can_not_decapsulate_low_order_x25519_encapsulated_key() {
+        let kem = X25519MlKem768Kem {};
+        let (private_key, public_key) = kem.generate_key_pair_data();
+        let (mut data, _) = kem.encapsulate(&public_key).unwrap();
+        data.truncate(ML_KEM_CIPHER_TEXT_NUM_BYTES);
+        data.extend_from_slice(&[0u8; X25519_NUM_BYTES]);
+
+        let result = kem.decapsulate(&private_key, &mut data).unwrap_err();
+
+        assert_eq!(KemError::InvalidPublicKey, result);
+    }
+
+    #[test]
+    fn can_not_decapsulate_truncated_key() {
+        let kem = X25519MlKem768Kem {};
+        let (private_key, _) = kem.generate_key_pair_data();
+        let mut data = vec![0u8; 1119];
+
+        let result = kem.decapsulate(&private_key, &mut data).unwrap_err();
+        assert_eq!(KemError::EncapsulatedKeyTooShort, result);
+    }
+
+    #[test]
+    fn can_not_use_wrong_length_public_key() {
+        let kem = X25519MlKem768Kem {};
+
+        let result = kem.encapsulate(&[0u8; 32]).unwrap_err();
+
+        assert_eq!(
+            KemError::PublicKeyWrongLength {
+                expected_length: 1216,
+                actual_length: 32
+            },
+            result
+        )
+    }
+
+    #[test]
+    fn can_not_use_wrong_length_private_key() {
+        let kem = X25519MlKem768Kem {};
+        let mut data = vec![0u8; 1120];
+
+        let result = kem.decapsulate(&[0u8; 31], &mut data).unwrap_err();
+
+        assert_eq!(
+            KemError::PrivateKeyWrongLength {
+                expected_length: 32,
+                actual_length: 31
+            },
+            result
+        )
+    }
+}
@@ -23,6 +23,7 @@
 //!     FormatType::Plain1,   // Format of input (plain text)
 //!     FormatType::Signal1,  // Format of output (cipher text)
 //!     &mut key.clone(),
+//!     &[],                 // Associated data (none)
 //!     &mut data
 //! )?;
 //!
@@ -42,6 +43,7 @@
 //!     FormatType::Signal1, // Format of input (cipher text)
 //!     FormatType::Plain1,  // Format of output (plain text)
 //!     &mut key.clone(),
+//!     &[],                 // Associated data (none)
 //!     &mut data
 //! )?;
 //!
@@ -50,25 +52,80 @@
 //! # }
 
 
+mod age_file;
 mod algo;
+mod diceware;
+mod fingerprint;
 mod format;
+mod handshake;
+mod kdf;
+mod kem;
+mod pake;
+mod sign;
+mod ssh;
 
-use crate::algo::select_algorithm;
+use crate::algo::{
+    push_counter_prefix, select_algorithm, take_counter_prefix, Algorithm, OtpAlgorithm,
+};
 use crate::format::select_format;
+use crate::kdf::{derive_sub_key, push_header_key, select_kdf, take_header_key};
+use crate::kem::select_kem;
+use crate::sign::select_signer;
+
+pub use crate::age_file::AgeError;
+
+pub use crate::algo::{
+    AlgoError, AlgoInfo, AlgoType, ALGORITHM_NAMES, DEFAULT_ALGO_NAME,
+    DEFAULT_COMPACT_TAG_NUM_BYTES, DEFAULT_PAD_NUM_BYTES,
+};
 
-pub use crate::algo::{AlgoError, AlgoType, ALGORITHM_NAMES, DEFAULT_ALGO_NAME};
+pub use crate::diceware::{
+    DEFAULT_NUM_WORDS as DEFAULT_PASSPHRASE_NUM_WORDS,
+    DEFAULT_SEPARATOR as DEFAULT_PASSPHRASE_SEPARATOR,
+};
+
+pub use crate::fingerprint::{FingerprintType, DEFAULT_FINGERPRINT_NAME, FINGERPRINT_NAMES};
 
 pub use crate::format::{
     FormatError, FormatType, ALL_FORMAT_NAMES, DEFAULT_CIPHER_FORMAT, DEFAULT_KEY_FORMAT,
     DEFAULT_PLAIN_FORMAT, KEY_FORMAT_NAMES,
 };
 
+pub use crate::handshake::{
+    Handshake, HandshakeError, MESSAGE_NUM_BYTES as HANDSHAKE_MESSAGE_NUM_BYTES,
+};
+
+pub use crate::kdf::{Argon2Params, KdfError, KdfType, KDF_NAMES};
+
+pub use crate::kem::{KemError, KemInfo, KemType, DEFAULT_KEM_NAME, KEM_NAMES};
+
+pub use crate::pake::{PakeError, PakeExchange, MESSAGE_NUM_BYTES as PAKE_MESSAGE_NUM_BYTES};
+
+pub use crate::sign::{SignError, SignInfo, SignType, DEFAULT_SIGN_NAME, SIGN_NAMES};
+
+pub use crate::ssh::SshError;
+
 
 #[derive(Debug)]
 pub enum SigliError {
+    Age(AgeError),
     Algo(AlgoError),
+    Kem(KemError),
+    Kdf(KdfError),
     MalformedKey(FormatError),
-    MalformedInput(FormatError)
+    MalformedInput(FormatError),
+    KeyCountMismatch {
+        expected_count: usize,
+        actual_count: usize,
+    },
+    Sign(SignError),
+    Ssh(SshError),
+    Handshake(HandshakeError),
+    Pake(PakeError),
+    /// None of the wrapped data keys in the message could be opened with the key given.
+    NotARecipient,
+    /// Sender signature was not made by any of the known sender keys.
+    UnknownSender,
 }
 
 impl std::fmt::Display for SigliError {
@@ -79,12 +136,71 @@ impl std::fmt::Display for SigliError {
 
 impl std::error::Error for SigliError {}
 
+impl std::convert::From<AgeError> for SigliError {
+    fn from(e: AgeError) -> Self {
+        SigliError::Age(e)
+    }
+}
+
 impl std::convert::From<AlgoError> for SigliError {
     fn from(e: AlgoError) -> Self {
         SigliError::Algo(e)
     }
 }
 
+impl std::convert::From<HandshakeError> for SigliError {
+    fn from(e: HandshakeError) -> Self {
+        SigliError::Handshake(e)
+    }
+}
+
+impl std::convert::From<KdfError> for SigliError {
+    fn from(e: KdfError) -> Self {
+        SigliError::Kdf(e)
+    }
+}
+
+impl std::convert::From<KemError> for SigliError {
+    fn from(e: KemError) -> Self {
+        SigliError::Kem(e)
+    }
+}
+
+impl std::convert::From<PakeError> for SigliError {
+    fn from(e: PakeError) -> Self {
+        SigliError::Pake(e)
+    }
+}
+
+impl std::convert::From<SignError> for SigliError {
+    fn from(e: SignError) -> Self {
+        SigliError::Sign(e)
+    }
+}
+
+impl std::convert::From<SshError> for SigliError {
+    fn from(e: SshError) -> Self {
+        SigliError::Ssh(e)
+    }
+}
+
+
+
+/// Describe an algorithm's key length, per-message overhead and properties.
+///
+/// # Example
+///
+/// ```rust
+/// use sigli::{AlgoType, algorithm_info};
+///
+/// let info = algorithm_info(AlgoType::Aes256Gcm);
+///
+/// // 16 byte authentication tag and 12 byte nonce.
+/// assert_eq!(28, info.overhead_num_bytes());
+/// ```
+pub fn algorithm_info(algo_type: AlgoType) -> AlgoInfo {
+    select_algorithm(algo_type).info()
+}
 
 
 /// Generate a new key.
@@ -132,8 +248,10 @@ pub fn generate_key(algo_type: AlgoType, key_format: FormatType) -> Result<Vec<u
 /// * `input_format` - Format used to unpack raw input data into input data.
 /// * `output_format` - Format used to pack output data input raw output data.
 /// * `key` - Raw key data. On successful return this will have been converted into key data.
+/// * `aad` - Associated data. Not included in the output, but must be identical when
+///   decrypting. Use an empty slice when there is none.
 /// * `data` - Raw data to decrypt. On successful return this will have been converted into
-/// raw output data.
+///   raw output data.
 ///
 /// # Example
 ///
@@ -158,6 +276,7 @@ pub fn generate_key(algo_type: AlgoType, key_format: FormatType) -> Result<Vec<u
 ///     FormatType::Plain1,  // Format of input (plain text)
 ///     FormatType::Signal1,  // Format of output (cipher text)
 ///     &mut key,
+///     &[],                 // Associated data (none)
 ///     &mut data
 /// )?;
 ///
@@ -174,18 +293,19 @@ pub fn encrypt(
     input_format: FormatType,
     output_format: FormatType,
     key: &mut Vec<u8>,
+    aad: &[u8],
     data: &mut Vec<u8>,
 ) -> Result<(), SigliError> {
 
     select_format(key_format)
         .unpack_input(key)
-        .map_err(|e| SigliError::MalformedKey(e))?;
+        .map_err(SigliError::MalformedKey)?;
 
     select_format(input_format)
         .unpack_input(data)
-        .map_err(|e| SigliError::MalformedInput(e))?;
+        .map_err(SigliError::MalformedInput)?;
 
-    select_algorithm(algorithm).encrypt_data(key, data)?;
+    select_algorithm(algorithm).encrypt_data(key, aad, data)?;
     select_format(output_format).pack_output(data);
 
     Ok(())
@@ -201,8 +321,10 @@ pub fn encrypt(
 /// * `input_format` - Format used to unpack raw input data into input data.
 /// * `output_format` - Format used to pack output data input raw output data.
 /// * `key` - Raw key data. On successful return this will have been converted into key data.
+/// * `aad` - Associated data. Not included in the output, but must be identical when
+///   decrypting. Use an empty slice when there is none.
 /// * `data` - Raw data to decrypt. On successful return this will have been converted into
-/// raw output data.
+///   raw output data.
 ///
 /// # Example
 ///
@@ -229,6 +351,7 @@ pub fn encrypt(
 ///     FormatType::Signal1,  // Format of input (cipher text)
 ///     FormatType::Plain1,  // Format of output (plain text)
 ///     &mut key,
+///     &[],                 // Associated data (none)
 ///     &mut data
 /// )?;
 ///
@@ -243,20 +366,2177 @@ pub fn decrypt(
     input_format: FormatType,
     output_format: FormatType,
     key: &mut Vec<u8>,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    select_algorithm(algorithm).decrypt_data(key, aad, data)?;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Encrypt message data in place using a sender ID and message counter in place of a random
+/// nonce.
+///
+/// The sender ID and counter are carried at the start of the cipher text, which is shorter
+/// than the twelve byte random nonce used by `encrypt`. Supported by the algorithms that use a
+/// twelve byte nonce (`aes128gcm`, `aes256gcm`, `aes128gcmsiv`, `aes256gcmsiv` and
+/// `chacha20poly1305`).
+///
+/// **The caller is responsible for never using the same sender ID and counter value twice with
+/// the same key.** Everyone sending with a shared key needs their own sender ID. Reusing a
+/// nonce with AES-GCM or ChaCha20-Poly1305 destroys confidentiality and authenticity for both
+/// messages.
+///
+/// # Arguments
+///
+/// * `algorithm` - Algorithm to use for encryption
+/// * `key_format` - Format used to unpack raw key into key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `key` - Raw key data. On successful return this will have been converted into key data.
+/// * `sender_id` - Sender ID, different for each sender using this key.
+/// * `counter` - Message counter, unique for this sender ID and key.
+/// * `aad` - Associated data. Not included in the output, but must be identical when
+///   decrypting. Use an empty slice when there is none.
+/// * `data` - Raw data to encrypt. On successful return this will have been converted into
+///   raw output data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, FormatType, decrypt_with_counter, encrypt_with_counter};
+///
+/// let key = "E1EB-4267-D828-2ADB-FF47-\
+///            E431-ABAF-FC2D-84E7-E045-\
+///            9CEE-2C39-487D-A576-ECF4-\
+///            FD53".as_bytes().to_vec();
+///
+/// let mut data = "test message 12. ".as_bytes().to_vec();
+///
+/// encrypt_with_counter(
+///     AlgoType::Aes256Gcm, // Encryption algorithm
+///     FormatType::Hex,     // Format of key
+///     FormatType::Plain1,  // Format of input (plain text)
+///     FormatType::Hex,     // Format of output (cipher text)
+///     &mut key.clone(),
+///     1,                   // Sender ID
+///     7,                   // Message counter
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// // The same key, sender ID and counter always give the same cipher text.
+/// assert_eq!(
+///     "0107-207F-2E38-5884-C31B-2729-8EEF-A986-936C-F6C3-09D8-F1C2-89BC-252B-B10A\n",
+///     std::str::from_utf8(&data)?
+/// );
+///
+/// decrypt_with_counter(
+///     AlgoType::Aes256Gcm, // Decryption algorithm
+///     FormatType::Hex,     // Format of key
+///     FormatType::Hex,     // Format of input (cipher text)
+///     FormatType::Plain1,  // Format of output (plain text)
+///     &mut key.clone(),
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// assert_eq!(&"test message 12. ".as_bytes().to_vec(), &data);
+/// # Ok(())
+/// # }
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn encrypt_with_counter(
+    algorithm: AlgoType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    key: &mut Vec<u8>,
+    sender_id: u8,
+    counter: u64,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    select_algorithm(algorithm).encrypt_data_with_counter(key, sender_id, counter, aad, data)?;
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Decrypt message data in place that was encrypted by `encrypt_with_counter`.
+///
+/// # Arguments
+///
+/// * `algorithm` - Algorithm to use for decryption
+/// * `key_format` - Format used to unpack raw key into key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `key` - Raw key data. On successful return this will have been converted into key data.
+/// * `aad` - Associated data. Not included in the output, but must be identical when
+///   decrypting. Use an empty slice when there is none.
+/// * `data` - Raw data to decrypt. On successful return this will have been converted into
+///   raw output data.
+pub fn decrypt_with_counter(
+    algorithm: AlgoType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    key: &mut Vec<u8>,
+    aad: &[u8],
     data: &mut Vec<u8>,
 ) -> Result<(), SigliError> {
 
     select_format(key_format)
         .unpack_input(key)
-        .map_err(|e| SigliError::MalformedKey(e))?;
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    select_algorithm(algorithm).decrypt_data_with_counter(key, aad, data)?;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+fn unpack_cascade_keys(
+    algorithms: &[AlgoType],
+    key_format: FormatType,
+    keys: &mut [Vec<u8>],
+) -> Result<(), SigliError> {
+    if algorithms.len() != keys.len() {
+        return Err(SigliError::KeyCountMismatch {
+            expected_count: algorithms.len(),
+            actual_count: keys.len(),
+        });
+    }
+
+    let key_format = select_format(key_format);
+    for key in keys.iter_mut() {
+        key_format.unpack_input(key).map_err(SigliError::MalformedKey)?;
+    }
+
+    Ok(())
+}
+
+
+/// Encrypt message data in place with several algorithms and keys, one layer after another.
+///
+/// The first algorithm is applied first using the first key, and its cipher text is then
+/// encrypted by the second algorithm using the second key, and so on. The layers work on
+/// raw bytes, so the input and output formats are only applied once. A message stays
+/// secret as long as any one of the layers is unbroken.
+///
+/// # Arguments
+///
+/// * `algorithms` - Algorithms to use for encryption, innermost layer first.
+/// * `key_format` - Format used to unpack raw keys into key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `keys` - Raw key data, one for each algorithm. On successful return these will have
+///   been converted into key data.
+/// * `aad` - Associated data. Authenticated by every layer. Use an empty slice when there
+///   is none.
+/// * `data` - Raw data to encrypt. On successful return this will have been converted into
+///   raw output data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, FormatType, decrypt_cascade, encrypt_cascade, generate_key};
+///
+/// let keys = vec![
+///     generate_key(AlgoType::Aes256Gcm, FormatType::Hex)?,
+///     generate_key(AlgoType::ChaCha20Poly1305, FormatType::Hex)?,
+/// ];
+///
+/// let mut data = "test message 12. ".as_bytes().to_vec();
+///
+/// encrypt_cascade(
+///     vec![AlgoType::Aes256Gcm, AlgoType::ChaCha20Poly1305], // Innermost layer first
+///     FormatType::Hex,     // Format of keys
+///     FormatType::Plain1,  // Format of input (plain text)
+///     FormatType::Signal1, // Format of output (cipher text)
+///     &mut keys.clone(),
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// decrypt_cascade(
+///     vec![AlgoType::Aes256Gcm, AlgoType::ChaCha20Poly1305], // Same order as encryption
+///     FormatType::Hex,     // Format of keys
+///     FormatType::Signal1, // Format of input (cipher text)
+///     FormatType::Plain1,  // Format of output (plain text)
+///     &mut keys.clone(),
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// assert_eq!(&"test message 12. ".as_bytes().to_vec(), &data);
+/// # Ok(())
+/// # }
+/// ```
+pub fn encrypt_cascade(
+    algorithms: Vec<AlgoType>,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    keys: &mut [Vec<u8>],
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    unpack_cascade_keys(&algorithms, key_format, keys)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    for (algorithm, key) in algorithms.into_iter().zip(keys.iter()) {
+        select_algorithm(algorithm).encrypt_data(key, aad, data)?;
+    }
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Decrypt message data in place that was encrypted by `encrypt_cascade`.
+///
+/// The layers are removed in reverse order, so `algorithms` and `keys` must be given in the
+/// same order that was used for encryption.
+///
+/// # Arguments
+///
+/// * `algorithms` - Algorithms used for encryption, innermost layer first.
+/// * `key_format` - Format used to unpack raw keys into key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `keys` - Raw key data, one for each algorithm. On successful return these will have
+///   been converted into key data.
+/// * `aad` - Associated data. Must be identical to that used when encrypting.
+/// * `data` - Raw data to decrypt. On successful return this will have been converted into
+///   raw output data.
+pub fn decrypt_cascade(
+    algorithms: Vec<AlgoType>,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    keys: &mut [Vec<u8>],
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    unpack_cascade_keys(&algorithms, key_format, keys)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    for (algorithm, key) in algorithms.into_iter().zip(keys.iter()).rev() {
+        select_algorithm(algorithm).decrypt_data(key, aad, data)?;
+    }
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Encrypt message data in place with a key derived from a passphrase.
+///
+/// The key is derived with Argon2id using a fresh random salt. The cost parameters and
+/// salt are placed in front of the cipher text, so decryption needs nothing but the
+/// passphrase. This adds the 16 byte salt and a few bytes of cost parameters (21 bytes in
+/// all with the default parameters) to the output of `encrypt`.
+///
+/// # Arguments
+///
+/// * `algorithm` - Algorithm to use for encryption. Must have a fixed key length.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `passphrase` - Passphrase to derive key from.
+/// * `params` - Argon2id cost parameters.
+/// * `aad` - Associated data. Not included in the output, but must be identical when
+///   decrypting. Use an empty slice when there is none.
+/// * `data` - Raw data to encrypt. On successful return this will have been converted into
+///   raw output data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, Argon2Params, FormatType, decrypt_with_passphrase,
+///             encrypt_with_passphrase};
+///
+/// let mut data = "test message 12. ".as_bytes().to_vec();
+///
+/// encrypt_with_passphrase(
+///     AlgoType::Aes256Gcm, // Encryption algorithm
+///     FormatType::Plain1,  // Format of input (plain text)
+///     FormatType::Signal1, // Format of output (cipher text)
+///     b"correct horse battery staple",
+///     &Argon2Params::default(),
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// decrypt_with_passphrase(
+///     AlgoType::Aes256Gcm, // Decryption algorithm
+///     FormatType::Signal1, // Format of input (cipher text)
+///     FormatType::Plain1,  // Format of output (plain text)
+///     b"correct horse battery staple",
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// assert_eq!(&"test message 12. ".as_bytes().to_vec(), &data);
+/// # Ok(())
+/// # }
+/// ```
+pub fn encrypt_with_passphrase(
+    algorithm: AlgoType,
+    input_format: FormatType,
+    output_format: FormatType,
+    passphrase: &[u8],
+    params: &Argon2Params,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
 
     select_format(input_format)
         .unpack_input(data)
-        .map_err(|e| SigliError::MalformedInput(e))?;
+        .map_err(SigliError::MalformedInput)?;
 
-    select_algorithm(algorithm).decrypt_data(key, data)?;
+    let algorithm = select_algorithm(algorithm);
+    let key_num_bytes = algorithm.info().key_num_bytes.ok_or(KdfError::KeyLengthNotFixed)?;
+    let mut header = Vec::new();
+    let key = push_header_key(params, passphrase, key_num_bytes, &mut header)?;
+    algorithm.encrypt_data(&key, aad, data)?;
+    header.append(data);
+    *data = header;
 
     select_format(output_format).pack_output(data);
 
     Ok(())
 }
+
+
+/// Decrypt message data in place that was encrypted by `encrypt_with_passphrase`.
+///
+/// # Arguments
+///
+/// * `algorithm` - Algorithm used for encryption.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `passphrase` - Passphrase used for encryption.
+/// * `aad` - Associated data. Must be identical to that used when encrypting.
+/// * `data` - Raw data to decrypt. On successful return this will have been converted into
+///   raw output data.
+pub fn decrypt_with_passphrase(
+    algorithm: AlgoType,
+    input_format: FormatType,
+    output_format: FormatType,
+    passphrase: &[u8],
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let algorithm = select_algorithm(algorithm);
+    let key_num_bytes = algorithm.info().key_num_bytes.ok_or(KdfError::KeyLengthNotFixed)?;
+    let key = take_header_key(passphrase, key_num_bytes, data)?;
+    algorithm.decrypt_data(&key, aad, data)?;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Derive a key from a passphrase and salt.
+///
+/// Unlike `generate_key`, the key is not random: anyone using the same key derivation
+/// function, parameters, passphrase and salt gets the same key. This allows sigli to share
+/// keys with systems that derive them from a passphrase. The key is only as strong as the
+/// passphrase.
+///
+/// # Arguments
+///
+/// * `algo_type` - Algorithm the key is for. Sets the key length.
+/// * `kdf_type` - Key derivation function and its parameters.
+/// * `key_format` - Format used to pack key.
+/// * `salt_format` - Format used to unpack raw salt into salt data.
+/// * `passphrase` - Passphrase to derive key from.
+/// * `salt` - Raw salt agreed with the other systems using this key. On successful return
+///   this will have been converted into salt data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, FormatType, KdfType, derive_key_from_passphrase};
+///
+/// let key = derive_key_from_passphrase(
+///     AlgoType::Aes256Gcm,                  // Algorithm to derive key for
+///     KdfType::Pbkdf2 { iterations: 4096 }, // Key derivation function
+///     FormatType::Hex,                      // Format of key
+///     FormatType::Raw,                      // Format of salt
+///     b"password",
+///     &mut b"salt".to_vec(),
+/// )?;
+///
+/// assert_eq!(
+///     "C5E4-78D5-9288-C841-AA53-0DB6-845C-4C8D-9628-93A0-01CE-4E11-A496-3873-AA98-134A\n",
+///     std::str::from_utf8(&key)?
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn derive_key_from_passphrase(
+    algo_type: AlgoType,
+    kdf_type: KdfType,
+    key_format: FormatType,
+    salt_format: FormatType,
+    passphrase: &[u8],
+    salt: &mut Vec<u8>,
+) -> Result<Vec<u8>, SigliError> {
+    select_format(salt_format)
+        .unpack_input(salt)
+        .map_err(SigliError::MalformedInput)?;
+
+    let key_num_bytes = select_algorithm(algo_type)
+        .info()
+        .key_num_bytes
+        .ok_or(KdfError::KeyLengthNotFixed)?;
+
+    let mut key = select_kdf(kdf_type).derive_key(passphrase, salt, key_num_bytes)?;
+
+    select_format(key_format).pack_output(&mut key);
+
+    Ok(key)
+}
+
+
+/// Derive a new key from a master key and a context label.
+///
+/// Uses HKDF-SHA256 with no salt and the label as info, so each label (for example a
+/// contact, channel or purpose) gives a different key of the length needed by `algo_type`.
+/// Derived keys can be handed out separately: knowing one derived key reveals nothing
+/// about the master key or any other derived key.
+///
+/// # Arguments
+///
+/// * `algo_type` - Algorithm the derived key is for. Sets the key length.
+/// * `key_format` - Format used to unpack the master key and pack the derived key.
+/// * `master_key` - Raw master key data. On successful return this will have been
+///   converted into master key data.
+/// * `label` - Context label. The same master key and label always give the same key.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, FormatType, derive_key};
+///
+/// let master_key = "E1EB-4267-D828-2ADB-FF47-\
+///                   E431-ABAF-FC2D-84E7-E045-\
+///                   9CEE-2C39-487D-A576-ECF4-\
+///                   FD53".as_bytes().to_vec();
+///
+/// let alice_key = derive_key(
+///     AlgoType::Aes256Gcm, // Algorithm to derive key for
+///     FormatType::Hex,     // Format of keys
+///     &mut master_key.clone(),
+///     b"contact alice",    // Context label
+/// )?;
+///
+/// let bob_key = derive_key(
+///     AlgoType::Aes256Gcm,
+///     FormatType::Hex,
+///     &mut master_key.clone(),
+///     b"contact bob",
+/// )?;
+///
+/// assert_ne!(alice_key, bob_key);
+/// # Ok(())
+/// # }
+/// ```
+pub fn derive_key(
+    algo_type: AlgoType,
+    key_format: FormatType,
+    master_key: &mut Vec<u8>,
+    label: &[u8],
+) -> Result<Vec<u8>, SigliError> {
+    let key_format = select_format(key_format);
+    key_format
+        .unpack_input(master_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    let key_num_bytes = select_algorithm(algo_type)
+        .info()
+        .key_num_bytes
+        .ok_or(KdfError::KeyLengthNotFixed)?;
+
+    let mut key = derive_sub_key(master_key, label, key_num_bytes)?;
+
+    key_format.pack_output(&mut key);
+
+    Ok(key)
+}
+
+
+/// Fingerprint a key, so two people can read it aloud to check they hold the same key.
+///
+/// The fingerprint is a SHA-256 hash of the key data, written as four groups of five
+/// letters (about 94 bits) or six words (about 77 bits). It works for any key: shared
+/// keys, public keys and private keys. Compare public keys rather than private keys.
+///
+/// # Arguments
+///
+/// * `fingerprint_type` - How to write the fingerprint.
+/// * `key_format` - Format used to unpack raw key into key data.
+/// * `key` - Raw key data. On successful return this will have been converted into key data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{FingerprintType, FormatType, fingerprint};
+///
+/// let key = "E1EB-4267-D828-2ADB-FF47-E431-ABAF-FC2D".as_bytes().to_vec();
+///
+/// let letters = fingerprint(FingerprintType::Letters, FormatType::Hex, &mut key.clone())?;
+/// let words = fingerprint(FingerprintType::Words, FormatType::Hex, &mut key.clone())?;
+///
+/// assert_eq!(4, letters.split(' ').count());
+/// assert_eq!(6, words.split(' ').count());
+/// # Ok(())
+/// # }
+/// ```
+pub fn fingerprint(
+    fingerprint_type: FingerprintType,
+    key_format: FormatType,
+    key: &mut Vec<u8>,
+) -> Result<String, SigliError> {
+    select_format(key_format)
+        .unpack_input(key)
+        .map_err(SigliError::MalformedKey)?;
+
+    Ok(crate::fingerprint::fingerprint(fingerprint_type, key))
+}
+
+
+/// Generate a diceware-style passphrase of random words from the EFF large word list.
+///
+/// Each word adds about 12.9 bits of entropy, see `passphrase_entropy_bits`. Words are
+/// chosen with the same random number generator as `generate_key`.
+///
+/// # Arguments
+///
+/// * `num_words` - Number of words in passphrase.
+/// * `separator` - Text placed between words.
+///
+/// # Example
+///
+/// ```rust
+/// use sigli::generate_passphrase;
+///
+/// let passphrase = generate_passphrase(6, " ");
+///
+/// // Prints something like "crummiest unbalance oat fossil bulge unsaved"
+/// println!("{}", passphrase);
+/// assert_eq!(6, passphrase.split(' ').count());
+/// ```
+pub fn generate_passphrase(num_words: usize, separator: &str) -> String {
+    diceware::generate_passphrase(num_words, separator)
+}
+
+
+/// Estimate the entropy in bits of a passphrase from `generate_passphrase`.
+///
+/// This assumes an attacker knows the word list and the number of words.
+pub fn passphrase_entropy_bits(num_words: usize) -> f64 {
+    diceware::entropy_bits(num_words)
+}
+
+
+/// Generate a new one-time pad for use with `AlgoType::Otp`.
+///
+/// # Arguments
+///
+/// * `num_bytes` - Length of pad. Each message uses 32 bytes plus its own length.
+/// * `key_format` - Format used to pack pad. `raw` or `hex` are recommended, because
+///   `signal1` is very slow for large pads.
+pub fn generate_pad(num_bytes: usize, key_format: FormatType) -> Result<Vec<u8>, SigliError> {
+    let mut pad = OtpAlgorithm::generate_pad_data(num_bytes);
+
+    select_format(key_format).pack_output(&mut pad);
+
+    Ok(pad)
+}
+
+
+/// Encrypt message data in place with a one-time pad.
+///
+/// Returns the pad offset of the first byte left unused by this message. The caller must
+/// record this and use it as the `pad_offset` of the next message, because reusing pad
+/// bytes destroys the secrecy of both messages. Decrypt with `decrypt` and `AlgoType::Otp`.
+///
+/// # Arguments
+///
+/// * `key_format` - Format used to unpack raw pad into pad data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `pad` - Raw pad data. On successful return this will have been converted into pad data.
+/// * `pad_offset` - Offset of first unused pad byte.
+/// * `aad` - Associated data. Not included in the output, but must be identical when
+///   decrypting. Use an empty slice when there is none.
+/// * `data` - Raw data to encrypt. On successful return this will have been converted into
+///   raw output data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, FormatType, decrypt, encrypt_with_pad, generate_pad};
+///
+/// let pad = generate_pad(1024, FormatType::Hex)?;
+///
+/// let mut data = "test message 12. ".as_bytes().to_vec();
+///
+/// let next_pad_offset = encrypt_with_pad(
+///     FormatType::Hex,     // Format of pad
+///     FormatType::Plain1,  // Format of input (plain text)
+///     FormatType::Signal1, // Format of output (cipher text)
+///     &mut pad.clone(),
+///     0,                   // First unused pad byte
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// decrypt(
+///     AlgoType::Otp,       // Decryption algorithm
+///     FormatType::Hex,     // Format of pad
+///     FormatType::Signal1, // Format of input (cipher text)
+///     FormatType::Plain1,  // Format of output (plain text)
+///     &mut pad.clone(),
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// assert_eq!(&"test message 12. ".as_bytes().to_vec(), &data);
+/// assert!(next_pad_offset > 32);
+/// # Ok(())
+/// # }
+/// ```
+pub fn encrypt_with_pad(
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    pad: &mut Vec<u8>,
+    pad_offset: u64,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<u64, SigliError> {
+
+    select_format(key_format)
+        .unpack_input(pad)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let next_pad_offset = OtpAlgorithm {}.encrypt_data_at_offset(pad, pad_offset, aad, data)?;
+    select_format(output_format).pack_output(data);
+
+    Ok(next_pad_offset)
+}
+
+
+/// Describe the key sizes of a key encapsulation mechanism.
+///
+/// # Example
+///
+/// ```rust
+/// use sigli::{KemType, kem_info};
+///
+/// let info = kem_info(KemType::X25519MlKem768);
+///
+/// // ML-KEM-768 cipher text and X25519 ephemeral public key.
+/// assert_eq!(1120, info.encapsulated_key_num_bytes);
+/// ```
+pub fn kem_info(kem_type: KemType) -> KemInfo {
+    select_kem(kem_type).info()
+}
+
+
+/// Generate a new key pair for receiving messages sent with `encrypt_to_recipient`.
+///
+/// Returns the private key then the public key, both packed with `key_format`. The
+/// public key can be given to anyone; the private key must be kept secret.
+///
+/// # Arguments
+///
+/// * `kem_type` - Key encapsulation mechanism to generate keys for.
+/// * `key_format` - Format used to pack keys.
+pub fn generate_key_pair(
+    kem_type: KemType,
+    key_format: FormatType,
+) -> Result<(Vec<u8>, Vec<u8>), SigliError> {
+    let (mut private_key, mut public_key) = select_kem(kem_type).generate_key_pair_data();
+
+    let key_format = select_format(key_format);
+    key_format.pack_output(&mut private_key);
+    key_format.pack_output(&mut public_key);
+
+    Ok((private_key, public_key))
+}
+
+
+/// Derive the key for `algorithm` from a shared secret agreed with a recipient.
+fn recipient_message_key(
+    algorithm: &dyn Algorithm,
+    shared_secret: &[u8],
+) -> Result<Vec<u8>, SigliError> {
+    let key_num_bytes = algorithm
+        .info()
+        .key_num_bytes
+        .ok_or(KdfError::KeyLengthNotFixed)?;
+    Ok(derive_sub_key(shared_secret, b"sigli recipient message key", key_num_bytes)?)
+}
+
+
+/// Encrypt message data in place for the owner of a public key.
+///
+/// A fresh shared secret is encapsulated for the recipient, and the message is encrypted
+/// with `algo_type` under a key derived from it. The encapsulated key is placed in front
+/// of the cipher text, so the output is `kem_info(kem_type).encapsulated_key_num_bytes`
+/// bytes longer than that of `encrypt`. Algorithms without a fixed key length (`otp`)
+/// can not be used.
+///
+/// # Arguments
+///
+/// * `algo_type` - Encryption algorithm.
+/// * `kem_type` - Key encapsulation mechanism of the recipient's key pair.
+/// * `key_format` - Format used to unpack raw public key into public key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `public_key` - Raw public key data. On successful return this will have been converted
+///   into public key data.
+/// * `aad` - Associated data. Not included in the output, but must be identical when
+///   decrypting. Use an empty slice when there is none.
+/// * `data` - Raw data to encrypt. On successful return this will have been converted into
+///   raw output data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, FormatType, KemType, decrypt_as_recipient, encrypt_to_recipient,
+///     generate_key_pair};
+///
+/// let (private_key, public_key) = generate_key_pair(KemType::X25519, FormatType::Hex)?;
+///
+/// let mut data = "test message 12. ".as_bytes().to_vec();
+///
+/// encrypt_to_recipient(
+///     AlgoType::Aes256Gcm, // Encryption algorithm
+///     KemType::X25519,     // Key encapsulation mechanism
+///     FormatType::Hex,     // Format of public key
+///     FormatType::Plain1,  // Format of input (plain text)
+///     FormatType::Raw,     // Format of output (cipher text)
+///     &mut public_key.clone(),
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// decrypt_as_recipient(
+///     AlgoType::Aes256Gcm, // Decryption algorithm
+///     KemType::X25519,     // Key encapsulation mechanism
+///     FormatType::Hex,     // Format of private key
+///     FormatType::Raw,     // Format of input (cipher text)
+///     FormatType::Plain1,  // Format of output (plain text)
+///     &mut private_key.clone(),
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// assert_eq!(&"test message 12. ".as_bytes().to_vec(), &data);
+/// # Ok(())
+/// # }
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn encrypt_to_recipient(
+    algo_type: AlgoType,
+    kem_type: KemType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    public_key: &mut Vec<u8>,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(public_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let (mut encapsulated_key, shared_secret) = select_kem(kem_type).encapsulate(public_key)?;
+    let algorithm = select_algorithm(algo_type);
+    let message_key = recipient_message_key(algorithm.as_ref(), &shared_secret)?;
+    algorithm.encrypt_data(&message_key, aad, data)?;
+    encapsulated_key.append(data);
+    *data = encapsulated_key;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Decrypt message data in place that was encrypted by `encrypt_to_recipient`.
+///
+/// # Arguments
+///
+/// * `algo_type` - Decryption algorithm. Must match the algorithm used to encrypt.
+/// * `kem_type` - Key encapsulation mechanism of the recipient's key pair.
+/// * `key_format` - Format used to unpack raw private key into private key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `private_key` - Raw private key data. On successful return this will have been
+///   converted into private key data.
+/// * `aad` - Associated data. Must be identical to that used when encrypting.
+/// * `data` - Raw data to decrypt. On successful return this will have been converted into
+///   raw output data.
+#[allow(clippy::too_many_arguments)]
+pub fn decrypt_as_recipient(
+    algo_type: AlgoType,
+    kem_type: KemType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    private_key: &mut Vec<u8>,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(private_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let shared_secret = select_kem(kem_type).decapsulate(private_key, data)?;
+    let algorithm = select_algorithm(algo_type);
+    let message_key = recipient_message_key(algorithm.as_ref(), &shared_secret)?;
+    algorithm.decrypt_data(&message_key, aad, data)?;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Associated data used when wrapping a data key, keeping wrapped keys apart from messages.
+const WRAPPED_KEY_AAD: &[u8] = b"sigli wrapped data key";
+
+
+/// Place the wrapped data keys in front of the message, each with its length.
+fn push_wrapped_keys(wrapped_keys: Vec<Vec<u8>>, data: &mut Vec<u8>) {
+    let num_wrapped_keys = wrapped_keys.len();
+    for wrapped_key in wrapped_keys.into_iter().rev() {
+        let num_bytes = wrapped_key.len();
+        data.splice(0..0, wrapped_key);
+        push_counter_prefix(num_bytes as u64, data);
+    }
+    push_counter_prefix(num_wrapped_keys as u64, data);
+}
+
+
+/// Remove the wrapped data keys added by `push_wrapped_keys` from the front of the message.
+fn take_wrapped_keys(data: &mut Vec<u8>) -> Result<Vec<Vec<u8>>, SigliError> {
+    let num_wrapped_keys = take_counter_prefix(data)?;
+    let mut wrapped_keys = Vec::new();
+    for _ in 0..num_wrapped_keys {
+        let num_bytes = take_counter_prefix(data)?;
+        if num_bytes > data.len() as u64 {
+            return Err(AlgoError::DecryptionFailed.into());
+        }
+        wrapped_keys.push(data.drain(..num_bytes as usize).collect());
+    }
+    Ok(wrapped_keys)
+}
+
+
+/// Open a wrapped data key, giving `None` when it was wrapped for a different key.
+fn open_wrapped_key(
+    algorithm: &dyn Algorithm,
+    key: &[u8],
+    mut wrapped_key: Vec<u8>,
+) -> Result<Option<Vec<u8>>, SigliError> {
+    match algorithm.decrypt_data(key, WRAPPED_KEY_AAD, &mut wrapped_key) {
+        Ok(()) => Ok(Some(wrapped_key)),
+        Err(AlgoError::DecryptionFailed) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+
+/// Decrypt the message body with the first data key that `unwrap` recovers.
+fn decrypt_wrapped(
+    algorithm: &dyn Algorithm,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+    mut unwrap: impl FnMut(Vec<u8>) -> Result<Option<Vec<u8>>, SigliError>,
+) -> Result<(), SigliError> {
+    for wrapped_key in take_wrapped_keys(data)? {
+        if let Some(data_key) = unwrap(wrapped_key)? {
+            algorithm.decrypt_data(&data_key, aad, data)?;
+            return Ok(());
+        }
+    }
+    Err(SigliError::NotARecipient)
+}
+
+
+/// Encrypt message data in place once for several shared keys and public keys.
+///
+/// The message is encrypted with `algo_type` under a fresh random data key. The data key
+/// is then wrapped (encrypted with `algo_type`) once for each shared key and once for each
+/// public key, and the wrapped keys are placed in front of the cipher text. Each wrapped
+/// key adds its length plus one or two bytes to the output: the key length plus the
+/// algorithm overhead for a shared key, and the encapsulated key length on top of that
+/// for a public key.
+///
+/// Everyone able to decrypt the message learns the data key, so any of them could
+/// change the message for the others. Use signatures when that matters.
+///
+/// # Arguments
+///
+/// * `algo_type` - Algorithm to use for encryption. Must have a fixed key length.
+/// * `kem_type` - Key encapsulation mechanism of the recipients' key pairs.
+/// * `key_format` - Format used to unpack raw shared keys and public keys into key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `keys` - Raw shared key data. On successful return these will have been converted into
+///   key data.
+/// * `public_keys` - Raw public key data. On successful return these will have been
+///   converted into public key data.
+/// * `aad` - Associated data. Not included in the output, but must be identical when
+///   decrypting. Use an empty slice when there is none.
+/// * `data` - Raw data to encrypt. On successful return this will have been converted into
+///   raw output data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, FormatType, KemType, decrypt_from_many, decrypt_from_many_as_recipient,
+///     encrypt_to_many, generate_key, generate_key_pair};
+///
+/// let alice_key = generate_key(AlgoType::Aes256Gcm, FormatType::Hex)?;
+/// let (bob_private_key, bob_public_key) = generate_key_pair(KemType::X25519, FormatType::Hex)?;
+///
+/// let mut data = "test message 12. ".as_bytes().to_vec();
+///
+/// encrypt_to_many(
+///     AlgoType::Aes256Gcm, // Encryption algorithm
+///     KemType::X25519,     // Key encapsulation mechanism
+///     FormatType::Hex,     // Format of keys
+///     FormatType::Plain1,  // Format of input (plain text)
+///     FormatType::Raw,     // Format of output (cipher text)
+///     &mut [alice_key.clone()],
+///     &mut [bob_public_key],
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// let mut alice_data = data.clone();
+/// decrypt_from_many(
+///     AlgoType::Aes256Gcm, // Decryption algorithm
+///     FormatType::Hex,     // Format of key
+///     FormatType::Raw,     // Format of input (cipher text)
+///     FormatType::Plain1,  // Format of output (plain text)
+///     &mut alice_key.clone(),
+///     &[],                 // Associated data (none)
+///     &mut alice_data
+/// )?;
+///
+/// decrypt_from_many_as_recipient(
+///     AlgoType::Aes256Gcm, // Decryption algorithm
+///     KemType::X25519,     // Key encapsulation mechanism
+///     FormatType::Hex,     // Format of private key
+///     FormatType::Raw,     // Format of input (cipher text)
+///     FormatType::Plain1,  // Format of output (plain text)
+///     &mut bob_private_key.clone(),
+///     &[],                 // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// assert_eq!(&"test message 12. ".as_bytes().to_vec(), &alice_data);
+/// assert_eq!(&"test message 12. ".as_bytes().to_vec(), &data);
+/// # Ok(())
+/// # }
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn encrypt_to_many(
+    algo_type: AlgoType,
+    kem_type: KemType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    keys: &mut [Vec<u8>],
+    public_keys: &mut [Vec<u8>],
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    let key_format = select_format(key_format);
+    for key in keys.iter_mut().chain(public_keys.iter_mut()) {
+        key_format.unpack_input(key).map_err(SigliError::MalformedKey)?;
+    }
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let algorithm = select_algorithm(algo_type);
+    if algorithm.info().key_num_bytes.is_none() {
+        return Err(KdfError::KeyLengthNotFixed.into());
+    }
+    let kem = select_kem(kem_type);
+    let data_key = algorithm.generate_key_data();
+
+    let mut wrapped_keys = Vec::with_capacity(keys.len() + public_keys.len());
+    for key in keys.iter() {
+        let mut wrapped_key = data_key.clone();
+        algorithm.encrypt_data(key, WRAPPED_KEY_AAD, &mut wrapped_key)?;
+        wrapped_keys.push(wrapped_key);
+    }
+    for public_key in public_keys.iter() {
+        let (mut encapsulated_key, shared_secret) = kem.encapsulate(public_key)?;
+        let message_key = recipient_message_key(algorithm.as_ref(), &shared_secret)?;
+        let mut wrapped_key = data_key.clone();
+        algorithm.encrypt_data(&message_key, WRAPPED_KEY_AAD, &mut wrapped_key)?;
+        encapsulated_key.append(&mut wrapped_key);
+        wrapped_keys.push(encapsulated_key);
+    }
+
+    algorithm.encrypt_data(&data_key, aad, data)?;
+    push_wrapped_keys(wrapped_keys, data);
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Decrypt message data in place that was encrypted by `encrypt_to_many`, using one of
+/// the shared keys it was encrypted for.
+///
+/// Each wrapped data key is tried in turn until one opens with `key`. Fails with
+/// `SigliError::NotARecipient` if none do.
+///
+/// # Arguments
+///
+/// * `algo_type` - Decryption algorithm. Must match the algorithm used to encrypt.
+/// * `key_format` - Format used to unpack raw key into key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `key` - Raw key data. On successful return this will have been converted into key data.
+/// * `aad` - Associated data. Must be identical to that used when encrypting.
+/// * `data` - Raw data to decrypt. On successful return this will have been converted into
+///   raw output data.
+pub fn decrypt_from_many(
+    algo_type: AlgoType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    key: &mut Vec<u8>,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let algorithm = select_algorithm(algo_type);
+    decrypt_wrapped(algorithm.as_ref(), aad, data, |wrapped_key| {
+        open_wrapped_key(algorithm.as_ref(), key, wrapped_key)
+    })?;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Decrypt message data in place that was encrypted by `encrypt_to_many`, using the
+/// private key for one of the public keys it was encrypted for.
+///
+/// Each wrapped data key is tried in turn until one opens with `private_key`. Fails with
+/// `SigliError::NotARecipient` if none do.
+///
+/// # Arguments
+///
+/// * `algo_type` - Decryption algorithm. Must match the algorithm used to encrypt.
+/// * `kem_type` - Key encapsulation mechanism of the recipient's key pair.
+/// * `key_format` - Format used to unpack raw private key into private key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `private_key` - Raw private key data. On successful return this will have been
+///   converted into private key data.
+/// * `aad` - Associated data. Must be identical to that used when encrypting.
+/// * `data` - Raw data to decrypt. On successful return this will have been converted into
+///   raw output data.
+#[allow(clippy::too_many_arguments)]
+pub fn decrypt_from_many_as_recipient(
+    algo_type: AlgoType,
+    kem_type: KemType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    private_key: &mut Vec<u8>,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(private_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let algorithm = select_algorithm(algo_type);
+    let kem = select_kem(kem_type);
+    decrypt_wrapped(algorithm.as_ref(), aad, data, |mut wrapped_key| {
+        let shared_secret = match kem.decapsulate(private_key, &mut wrapped_key) {
+            Ok(shared_secret) => shared_secret,
+            Err(e @ KemError::PrivateKeyWrongLength { .. }) => return Err(e.into()),
+            // Shared key entries and garbage are not recipient entries.
+            Err(_) => return Ok(None),
+        };
+        let message_key = recipient_message_key(algorithm.as_ref(), &shared_secret)?;
+        open_wrapped_key(algorithm.as_ref(), &message_key, wrapped_key)
+    })?;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Describe the key and signature sizes of a signature scheme.
+pub fn sign_info(sign_type: SignType) -> SignInfo {
+    select_signer(sign_type).info()
+}
+
+
+/// Generate a new key pair for signing messages.
+///
+/// Returns the private key then the public key, both packed with `key_format`. The
+/// public key can be given to anyone who needs to verify messages; the private key must
+/// be kept secret.
+///
+/// # Arguments
+///
+/// * `sign_type` - Signature scheme to generate keys for.
+/// * `key_format` - Format used to pack keys.
+pub fn generate_sign_key_pair(
+    sign_type: SignType,
+    key_format: FormatType,
+) -> Result<(Vec<u8>, Vec<u8>), SigliError> {
+    let (mut private_key, mut public_key) = select_signer(sign_type).generate_key_pair_data();
+
+    let key_format = select_format(key_format);
+    key_format.pack_output(&mut private_key);
+    key_format.pack_output(&mut public_key);
+
+    Ok((private_key, public_key))
+}
+
+
+/// Sign message data in place, attaching the signature.
+///
+/// The message is not encrypted. The signature is placed in front of the message, so the
+/// output is `sign_info(sign_type).signature_num_bytes` bytes longer than the input.
+///
+/// # Arguments
+///
+/// * `sign_type` - Signature scheme.
+/// * `key_format` - Format used to unpack raw private key into private key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `private_key` - Raw private key data. On successful return this will have been
+///   converted into private key data.
+/// * `data` - Raw data to sign. On successful return this will have been converted into
+///   raw output data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{FormatType, SignType, generate_sign_key_pair, sign, verify};
+///
+/// let (private_key, public_key) = generate_sign_key_pair(SignType::Ed25519, FormatType::Hex)?;
+///
+/// let mut data = "test message 12. ".as_bytes().to_vec();
+///
+/// sign(
+///     SignType::Ed25519,   // Signature scheme
+///     FormatType::Hex,     // Format of private key
+///     FormatType::Plain1,  // Format of input (message)
+///     FormatType::Signal1, // Format of output (signed message)
+///     &mut private_key.clone(),
+///     &mut data
+/// )?;
+///
+/// verify(
+///     SignType::Ed25519,   // Signature scheme
+///     FormatType::Hex,     // Format of public key
+///     FormatType::Signal1, // Format of input (signed message)
+///     FormatType::Plain1,  // Format of output (message)
+///     &mut public_key.clone(),
+///     &mut data
+/// )?;
+///
+/// assert_eq!(&"test message 12. ".as_bytes().to_vec(), &data);
+/// # Ok(())
+/// # }
+/// ```
+pub fn sign(
+    sign_type: SignType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    private_key: &mut Vec<u8>,
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(private_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let signature = select_signer(sign_type).sign(private_key, data)?;
+    data.splice(0..0, signature);
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Verify message data in place that was signed by `sign`, removing the signature.
+///
+/// # Arguments
+///
+/// * `sign_type` - Signature scheme.
+/// * `key_format` - Format used to unpack raw public key into public key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `public_key` - Raw public key data of the signer. On successful return this will have
+///   been converted into public key data.
+/// * `data` - Raw signed data to verify. On successful return this will have been converted
+///   into raw output data.
+pub fn verify(
+    sign_type: SignType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    public_key: &mut Vec<u8>,
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(public_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let signer = select_signer(sign_type);
+    let signature_num_bytes = signer.info().signature_num_bytes;
+    if data.len() < signature_num_bytes {
+        return Err(SignError::SignatureWrongLength.into());
+    }
+    let message = data.split_off(signature_num_bytes);
+    signer.verify(public_key, &message, data)?;
+    *data = message;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Sign message data, returning a detached signature.
+///
+/// The message is left as it is and can be sent separately from the signature.
+///
+/// # Arguments
+///
+/// * `sign_type` - Signature scheme.
+/// * `key_format` - Format used to unpack raw private key into private key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `signature_format` - Format used to pack the signature.
+/// * `private_key` - Raw private key data. On successful return this will have been
+///   converted into private key data.
+/// * `data` - Raw data to sign. On successful return this will have been converted into
+///   input data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{FormatType, SignType, generate_sign_key_pair, sign_detached, verify_detached};
+///
+/// let (private_key, public_key) = generate_sign_key_pair(SignType::Ed25519, FormatType::Hex)?;
+///
+/// let message = "test message 12. ".as_bytes().to_vec();
+///
+/// let signature = sign_detached(
+///     SignType::Ed25519,   // Signature scheme
+///     FormatType::Hex,     // Format of private key
+///     FormatType::Plain1,  // Format of input (message)
+///     FormatType::Signal1, // Format of signature
+///     &mut private_key.clone(),
+///     &mut message.clone()
+/// )?;
+///
+/// verify_detached(
+///     SignType::Ed25519,   // Signature scheme
+///     FormatType::Hex,     // Format of public key
+///     FormatType::Plain1,  // Format of input (message)
+///     FormatType::Signal1, // Format of signature
+///     &mut public_key.clone(),
+///     &mut signature.clone(),
+///     &mut message.clone()
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn sign_detached(
+    sign_type: SignType,
+    key_format: FormatType,
+    input_format: FormatType,
+    signature_format: FormatType,
+    private_key: &mut Vec<u8>,
+    data: &mut Vec<u8>,
+) -> Result<Vec<u8>, SigliError> {
+
+    select_format(key_format)
+        .unpack_input(private_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let mut signature = select_signer(sign_type).sign(private_key, data)?;
+
+    select_format(signature_format).pack_output(&mut signature);
+
+    Ok(signature)
+}
+
+
+/// Verify a detached signature made by `sign_detached`.
+///
+/// # Arguments
+///
+/// * `sign_type` - Signature scheme.
+/// * `key_format` - Format used to unpack raw public key into public key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `signature_format` - Format used to unpack the raw signature.
+/// * `public_key` - Raw public key data of the signer. On successful return this will have
+///   been converted into public key data.
+/// * `signature` - Raw signature. On successful return this will have been converted into
+///   signature data.
+/// * `data` - Raw data that was signed. On successful return this will have been converted
+///   into input data.
+pub fn verify_detached(
+    sign_type: SignType,
+    key_format: FormatType,
+    input_format: FormatType,
+    signature_format: FormatType,
+    public_key: &mut Vec<u8>,
+    signature: &mut Vec<u8>,
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(public_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(signature_format)
+        .unpack_input(signature)
+        .map_err(SigliError::MalformedInput)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    // Packing as signal1 drops trailing zero bytes, so restore them. A signature that was
+    // really truncated still fails to verify.
+    let signer = select_signer(sign_type);
+    let signature_num_bytes = signer.info().signature_num_bytes;
+    if signature.len() < signature_num_bytes {
+        signature.resize(signature_num_bytes, 0);
+    }
+
+    signer.verify(public_key, data, signature)?;
+
+    Ok(())
+}
+
+
+/// Data covered by a sender signature: a label, the associated data and the message.
+///
+/// The label keeps these signatures apart from those made by `sign`.
+fn sender_signed_data(aad: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut signed_data = aad.to_vec();
+    signed_data.extend_from_slice(message);
+    push_counter_prefix(aad.len() as u64, &mut signed_data);
+    signed_data.splice(0..0, b"sigli sender signature".iter().copied());
+    signed_data
+}
+
+
+/// Sign message data in place before encryption, so recipients can tell who sent it.
+///
+/// The signature covers the message and `aad`, and is placed in front of the message. The
+/// output is raw bytes, ready to be encrypted by any of the encrypt functions with
+/// `FormatType::Raw` as the input format. After decrypting, use `check_sender_signature`
+/// to remove and check the signature.
+///
+/// Unlike the key used for encryption, which everyone in a group may share, only the
+/// sender holds the private signing key, so a signed message can not be forged by other
+/// members of the group.
+///
+/// # Arguments
+///
+/// * `sign_type` - Signature scheme.
+/// * `key_format` - Format used to unpack raw private key into private key data.
+/// * `input_format` - Format used to unpack raw input data into input data.
+/// * `private_key` - Raw private signing key data of the sender. On successful return this
+///   will have been converted into private key data.
+/// * `aad` - Associated data. Also given to encryption, and must be identical when
+///   checking. Use an empty slice when there is none.
+/// * `data` - Raw data to sign. On successful return this will have been converted into
+///   the signature followed by the message.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, FormatType, SignType, attach_sender_signature, check_sender_signature,
+///     decrypt, encrypt, generate_key, generate_sign_key_pair};
+///
+/// let key = generate_key(AlgoType::Aes256Gcm, FormatType::Hex)?;
+/// let (alice_private_key, alice_public_key) =
+///     generate_sign_key_pair(SignType::Ed25519, FormatType::Hex)?;
+/// let (_, bob_public_key) = generate_sign_key_pair(SignType::Ed25519, FormatType::Hex)?;
+///
+/// let mut data = "test message 12. ".as_bytes().to_vec();
+///
+/// attach_sender_signature(
+///     SignType::Ed25519,  // Signature scheme
+///     FormatType::Hex,    // Format of private key
+///     FormatType::Plain1, // Format of input (plain text)
+///     &mut alice_private_key.clone(),
+///     &[],                // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// encrypt(AlgoType::Aes256Gcm, FormatType::Hex, FormatType::Raw, FormatType::Signal1,
+///     &mut key.clone(), &[], &mut data)?;
+///
+/// decrypt(AlgoType::Aes256Gcm, FormatType::Hex, FormatType::Signal1, FormatType::Raw,
+///     &mut key.clone(), &[], &mut data)?;
+///
+/// let sender = check_sender_signature(
+///     SignType::Ed25519,  // Signature scheme
+///     FormatType::Hex,    // Format of public keys
+///     FormatType::Plain1, // Format of output (plain text)
+///     &mut [bob_public_key, alice_public_key],
+///     &[],                // Associated data (none)
+///     &mut data
+/// )?;
+///
+/// assert_eq!(1, sender);
+/// assert_eq!(&"test message 12. ".as_bytes().to_vec(), &data);
+/// # Ok(())
+/// # }
+/// ```
+pub fn attach_sender_signature(
+    sign_type: SignType,
+    key_format: FormatType,
+    input_format: FormatType,
+    private_key: &mut Vec<u8>,
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+
+    select_format(key_format)
+        .unpack_input(private_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    let signature = select_signer(sign_type).sign(private_key, &sender_signed_data(aad, data))?;
+    data.splice(0..0, signature);
+
+    Ok(())
+}
+
+
+/// Remove and check the sender signature added by `attach_sender_signature`.
+///
+/// Call after decrypting with `FormatType::Raw` as the output format. Returns the index in
+/// `public_keys` of the sender whose key verified the signature, or fails with
+/// `SigliError::UnknownSender` if none did.
+///
+/// # Arguments
+///
+/// * `sign_type` - Signature scheme.
+/// * `key_format` - Format used to unpack raw public keys into public key data.
+/// * `output_format` - Format used to pack output data input raw output data.
+/// * `public_keys` - Raw public key data of the known senders. On successful return these
+///   will have been converted into public key data.
+/// * `aad` - Associated data. Must be identical to that used when signing.
+/// * `data` - Signature followed by the message. On successful return this will have been
+///   converted into raw output data.
+pub fn check_sender_signature(
+    sign_type: SignType,
+    key_format: FormatType,
+    output_format: FormatType,
+    public_keys: &mut [Vec<u8>],
+    aad: &[u8],
+    data: &mut Vec<u8>,
+) -> Result<usize, SigliError> {
+
+    let key_format = select_format(key_format);
+    for public_key in public_keys.iter_mut() {
+        key_format.unpack_input(public_key).map_err(SigliError::MalformedKey)?;
+    }
+
+    let signer = select_signer(sign_type);
+    let signature_num_bytes = signer.info().signature_num_bytes;
+    if data.len() < signature_num_bytes {
+        return Err(SignError::SignatureWrongLength.into());
+    }
+    let message = data.split_off(signature_num_bytes);
+    let signed_data = sender_signed_data(aad, &message);
+
+    let mut sender = None;
+    for (idx, public_key) in public_keys.iter().enumerate() {
+        match signer.verify(public_key, &signed_data, data) {
+            Ok(()) => {
+                sender = Some(idx);
+                break;
+            }
+            Err(SignError::VerificationFailed) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let sender = sender.ok_or(SigliError::UnknownSender)?;
+    *data = message;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(sender)
+}
+
+
+/// Start a password-authenticated key exchange (SPAKE2) from a shared code word.
+///
+/// Both sides start with the same code word, which can be short, and send each other the
+/// returned message. `finish_pake` then turns the other side's message into a full strength
+/// key. Someone watching or changing the messages gets a single guess at the code word per
+/// exchange, and can not test guesses offline.
+///
+/// # Arguments
+///
+/// * `code` - Code word agreed by both sides, for example over the phone.
+/// * `output_format` - Format used to pack the message.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{AlgoType, FormatType, finish_pake, start_pake};
+///
+/// let (exchange_a, mut message_a) = start_pake(b"purple monkey", FormatType::Signal1)?;
+/// let (exchange_b, mut message_b) = start_pake(b"purple monkey", FormatType::Signal1)?;
+///
+/// let key_a = finish_pake(
+///     exchange_a,
+///     AlgoType::Aes256Gcm, // Algorithm to make key for
+///     FormatType::Hex,     // Format of key
+///     FormatType::Signal1, // Format of message from other side
+///     &mut message_b
+/// )?;
+///
+/// let key_b = finish_pake(
+///     exchange_b,
+///     AlgoType::Aes256Gcm,
+///     FormatType::Hex,
+///     FormatType::Signal1,
+///     &mut message_a
+/// )?;
+///
+/// assert_eq!(key_a, key_b);
+/// # Ok(())
+/// # }
+/// ```
+pub fn start_pake(
+    code: &[u8],
+    output_format: FormatType,
+) -> Result<(PakeExchange, Vec<u8>), SigliError> {
+    let (exchange, mut message) = crate::pake::start(code);
+
+    select_format(output_format).pack_output(&mut message);
+
+    Ok((exchange, message))
+}
+
+
+/// Finish a password-authenticated key exchange started by `start_pake`.
+///
+/// Returns the key for `algo_type`, derived from the shared secret with HKDF-SHA256. Both
+/// sides get the same key only if they used the same code word; compare key fingerprints
+/// to confirm.
+///
+/// # Arguments
+///
+/// * `exchange` - Exchange returned by `start_pake`.
+/// * `algo_type` - Algorithm the key is for. Sets the key length.
+/// * `key_format` - Format used to pack the key.
+/// * `input_format` - Format used to unpack the message from the other side.
+/// * `message` - Raw message from the other side. On successful return this will have been
+///   converted into message data.
+pub fn finish_pake(
+    exchange: PakeExchange,
+    algo_type: AlgoType,
+    key_format: FormatType,
+    input_format: FormatType,
+    message: &mut Vec<u8>,
+) -> Result<Vec<u8>, SigliError> {
+    unpack_exchange_message(input_format, crate::pake::MESSAGE_NUM_BYTES, message)?;
+
+    let shared_secret = crate::pake::finish(exchange, message)?;
+
+    let mut key = agreed_key(algo_type, &shared_secret, b"sigli pake key")?;
+
+    select_format(key_format).pack_output(&mut key);
+
+    Ok(key)
+}
+
+
+/// Unpack a fixed length key exchange message.
+fn unpack_exchange_message(
+    input_format: FormatType,
+    num_bytes: usize,
+    message: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+    select_format(input_format)
+        .unpack_input(message)
+        .map_err(SigliError::MalformedInput)?;
+
+    // Packing as signal1 drops trailing zero bytes, so restore them.
+    if message.len() < num_bytes {
+        message.resize(num_bytes, 0);
+    }
+    Ok(())
+}
+
+
+/// Derive the key for `algo_type` from the secret agreed by a key exchange.
+fn agreed_key(
+    algo_type: AlgoType,
+    shared_secret: &[u8],
+    label: &[u8],
+) -> Result<Vec<u8>, SigliError> {
+    let key_num_bytes = select_algorithm(algo_type)
+        .info()
+        .key_num_bytes
+        .ok_or(KdfError::KeyLengthNotFixed)?;
+    Ok(derive_sub_key(shared_secret, label, key_num_bytes)?)
+}
+
+
+/// Start a Noise KK handshake with a contact whose X25519 public key is already known.
+///
+/// Returns the handshake state and the message to send to the contact, who answers it with
+/// `respond_to_handshake`. `finish_handshake` then checks the answer and returns the session
+/// key. Both sides prove they hold the private key matching the public key the other side
+/// expects, and the session key comes from fresh ephemeral keys, so it stays secret even if
+/// both private keys are later stolen.
+///
+/// # Arguments
+///
+/// * `key_format` - Format used to unpack the keys.
+/// * `output_format` - Format used to pack the message.
+/// * `private_key` - Our X25519 private key, as made by `generate_key_pair` with
+///   `KemType::X25519`. On successful return this will have been converted into key data.
+/// * `remote_public_key` - Contact's X25519 public key. On successful return this will
+///   have been converted into key data.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{
+///     finish_handshake, generate_key_pair, initiate_handshake, respond_to_handshake, AlgoType,
+///     FormatType, KemType,
+/// };
+///
+/// let (alice_private, alice_public) = generate_key_pair(KemType::X25519, FormatType::Hex)?;
+/// let (bob_private, bob_public) = generate_key_pair(KemType::X25519, FormatType::Hex)?;
+///
+/// let (handshake, mut message) = initiate_handshake(
+///     FormatType::Hex,     // Format of keys
+///     FormatType::Signal1, // Format of message
+///     &mut alice_private.clone(),
+///     &mut bob_public.clone()
+/// )?;
+///
+/// let (mut response, bob_key) = respond_to_handshake(
+///     AlgoType::Aes256Gcm, // Algorithm to make key for
+///     FormatType::Hex,     // Format of keys
+///     FormatType::Signal1, // Format of message from initiator
+///     FormatType::Signal1, // Format of response
+///     &mut bob_private.clone(),
+///     &mut alice_public.clone(),
+///     &mut message
+/// )?;
+///
+/// let alice_key = finish_handshake(
+///     handshake,
+///     AlgoType::Aes256Gcm,
+///     FormatType::Hex,
+///     FormatType::Signal1,
+///     &mut response
+/// )?;
+///
+/// assert_eq!(alice_key, bob_key);
+/// # Ok(())
+/// # }
+/// ```
+pub fn initiate_handshake(
+    key_format: FormatType,
+    output_format: FormatType,
+    private_key: &mut Vec<u8>,
+    remote_public_key: &mut Vec<u8>,
+) -> Result<(Handshake, Vec<u8>), SigliError> {
+    let key_format = select_format(key_format);
+    key_format.unpack_input(private_key).map_err(SigliError::MalformedKey)?;
+    key_format.unpack_input(remote_public_key).map_err(SigliError::MalformedKey)?;
+
+    let (handshake, mut message) = crate::handshake::initiate(private_key, remote_public_key)?;
+
+    select_format(output_format).pack_output(&mut message);
+
+    Ok((handshake, message))
+}
+
+
+/// Answer a handshake started by `initiate_handshake`.
+///
+/// Returns the message to send back to the initiator and the session key, which is only
+/// returned once the initiator has proved it holds the private key for `remote_public_key`.
+///
+/// # Arguments
+///
+/// * `algo_type` - Algorithm the key is for. Sets the key length.
+/// * `key_format` - Format used to unpack our keys and pack the session key.
+/// * `input_format` - Format used to unpack the initiator's message.
+/// * `output_format` - Format used to pack the response.
+/// * `private_key` - Our X25519 private key. On successful return this will have been
+///   converted into key data.
+/// * `remote_public_key` - Initiator's X25519 public key. On successful return this will
+///   have been converted into key data.
+/// * `message` - Message from the initiator. On successful return this will have been
+///   converted into message data.
+#[allow(clippy::too_many_arguments)]
+pub fn respond_to_handshake(
+    algo_type: AlgoType,
+    key_format: FormatType,
+    input_format: FormatType,
+    output_format: FormatType,
+    private_key: &mut Vec<u8>,
+    remote_public_key: &mut Vec<u8>,
+    message: &mut Vec<u8>,
+) -> Result<(Vec<u8>, Vec<u8>), SigliError> {
+    let key_format = select_format(key_format);
+    key_format.unpack_input(private_key).map_err(SigliError::MalformedKey)?;
+    key_format.unpack_input(remote_public_key).map_err(SigliError::MalformedKey)?;
+    unpack_exchange_message(input_format, crate::handshake::MESSAGE_NUM_BYTES, message)?;
+
+    let (mut response, shared_secret) =
+        crate::handshake::respond(private_key, remote_public_key, message)?;
+
+    select_format(output_format).pack_output(&mut response);
+
+    let mut key = agreed_key(algo_type, &shared_secret, b"sigli handshake key")?;
+    key_format.pack_output(&mut key);
+
+    Ok((response, key))
+}
+
+
+/// Finish a handshake started by `initiate_handshake` with the responder's message.
+///
+/// Returns the session key for `algo_type`, the same key the responder got.
+///
+/// # Arguments
+///
+/// * `handshake` - Handshake returned by `initiate_handshake`.
+/// * `algo_type` - Algorithm the key is for. Sets the key length.
+/// * `key_format` - Format used to pack the session key.
+/// * `input_format` - Format used to unpack the responder's message.
+/// * `message` - Message from the responder. On successful return this will have been
+///   converted into message data.
+pub fn finish_handshake(
+    handshake: Handshake,
+    algo_type: AlgoType,
+    key_format: FormatType,
+    input_format: FormatType,
+    message: &mut Vec<u8>,
+) -> Result<Vec<u8>, SigliError> {
+    unpack_exchange_message(input_format, crate::handshake::MESSAGE_NUM_BYTES, message)?;
+
+    let shared_secret = crate::handshake::finish(handshake, message)?;
+
+    let mut key = agreed_key(algo_type, &shared_secret, b"sigli handshake key")?;
+
+    select_format(key_format).pack_output(&mut key);
+
+    Ok(key)
+}
+
+
+/// Whether `data` is an age v1 file, binary or ASCII armored, rather than a sigli message.
+///
+/// # Example
+///
+/// ```rust
+/// use sigli::is_age_file;
+///
+/// assert!(is_age_file(b"age-encryption.org/v1\n-> X25519 ..."));
+/// assert!(!is_age_file(b"ZKCNU ZOSJI INMQH"));
+/// ```
+pub fn is_age_file(data: &[u8]) -> bool {
+    crate::age_file::is_age_file(data)
+}
+
+
+/// Text of a key, if it is written in age's own form rather than a sigli key format.
+fn age_key_text<'a>(key: &'a [u8], prefix: &str) -> Option<&'a str> {
+    std::str::from_utf8(key).ok().filter(|text| text.contains(prefix))
+}
+
+
+/// Write an x25519 public key from `generate_key_pair` as an age recipient (`age1...`), so
+/// age users can encrypt to it.
+///
+/// # Arguments
+///
+/// * `key_format` - Format used to unpack the public key.
+/// * `public_key` - Public key. On successful return this will have been converted into key
+///   data.
+pub fn age_recipient(
+    key_format: FormatType,
+    public_key: &mut Vec<u8>,
+) -> Result<String, SigliError> {
+    select_format(key_format)
+        .unpack_input(public_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    Ok(crate::age_file::recipient_string(public_key)?)
+}
+
+
+/// Write an x25519 private key from `generate_key_pair` as an age identity
+/// (`AGE-SECRET-KEY-1...`), so age can decrypt files sent to it.
+///
+/// # Arguments
+///
+/// * `key_format` - Format used to unpack the private key.
+/// * `private_key` - Private key. On successful return this will have been converted into
+///   key data.
+pub fn age_identity(
+    key_format: FormatType,
+    private_key: &mut Vec<u8>,
+) -> Result<String, SigliError> {
+    select_format(key_format)
+        .unpack_input(private_key)
+        .map_err(SigliError::MalformedKey)?;
+
+    Ok(crate::age_file::identity_string(private_key)?)
+}
+
+
+/// Encrypt to an age v1 file that age users can open.
+///
+/// Each public key is either an age recipient (`age1...`) or an x25519 public key from
+/// `generate_key_pair`, which is used as an age recipient. Without public keys, the file is
+/// encrypted to `passphrase` with an age scrypt stanza instead. age does not allow both, so
+/// giving public keys and a passphrase is an error.
+///
+/// # Arguments
+///
+/// * `key_format` - Format used to unpack sigli public keys.
+/// * `input_format` - Format used to unpack the plain text. Use `FormatType::Raw` for files
+///   that age users will read.
+/// * `armor` - Write an ASCII armored file instead of a binary one.
+/// * `public_keys` - Recipients' public keys. On successful return sigli keys will have been
+///   converted into key data.
+/// * `passphrase` - Passphrase to use instead of public keys.
+/// * `data` - Raw plain text. On successful return this will have been converted into the
+///   age file.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{decrypt_age, encrypt_age, generate_key_pair, FormatType, KemType};
+///
+/// let (private_key, public_key) = generate_key_pair(KemType::X25519, FormatType::Hex)?;
+///
+/// let mut data = b"hello age".to_vec();
+/// encrypt_age(
+///     FormatType::Hex, // Format of public keys
+///     FormatType::Raw, // Format of plain text
+///     true,            // ASCII armor
+///     &mut [public_key],
+///     None,
+///     &mut data
+/// )?;
+/// assert!(data.starts_with(b"-----BEGIN AGE ENCRYPTED FILE-----"));
+///
+/// decrypt_age(FormatType::Hex, FormatType::Raw, &mut [private_key], None, &mut data)?;
+/// assert_eq!(b"hello age".to_vec(), data);
+/// # Ok(())
+/// # }
+/// ```
+pub fn encrypt_age(
+    key_format: FormatType,
+    input_format: FormatType,
+    armor: bool,
+    public_keys: &mut [Vec<u8>],
+    passphrase: Option<&str>,
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+    let key_format = select_format(key_format);
+    let mut recipients = Vec::with_capacity(public_keys.len());
+    for public_key in public_keys.iter_mut() {
+        if let Some(text) = age_key_text(public_key, crate::age_file::RECIPIENT_PREFIX) {
+            recipients.push(crate::age_file::parse_recipient(text)?);
+        } else {
+            key_format.unpack_input(public_key).map_err(SigliError::MalformedKey)?;
+            let text = crate::age_file::recipient_string(public_key)?;
+            recipients.push(crate::age_file::parse_recipient(&text)?);
+        }
+    }
+
+    select_format(input_format)
+        .unpack_input(data)
+        .map_err(SigliError::MalformedInput)?;
+
+    *data = crate::age_file::encrypt(&recipients, passphrase, armor, data)?;
+
+    Ok(())
+}
+
+
+/// Decrypt an age v1 file, binary or ASCII armored.
+///
+/// Each private key is either an age identity file (`AGE-SECRET-KEY-1...` lines) or an x25519
+/// private key from `generate_key_pair`. Files encrypted to a passphrase are opened with
+/// `passphrase` instead.
+///
+/// # Arguments
+///
+/// * `key_format` - Format used to unpack sigli private keys.
+/// * `output_format` - Format used to pack the plain text. Use `FormatType::Raw` for files
+///   from age users.
+/// * `private_keys` - Our private keys. On successful return sigli keys will have been
+///   converted into key data.
+/// * `passphrase` - Passphrase for files encrypted to one.
+/// * `data` - age file. On successful return this will have been converted into the plain
+///   text.
+pub fn decrypt_age(
+    key_format: FormatType,
+    output_format: FormatType,
+    private_keys: &mut [Vec<u8>],
+    passphrase: Option<&str>,
+    data: &mut Vec<u8>,
+) -> Result<(), SigliError> {
+    let key_format = select_format(key_format);
+    let mut identities = Vec::with_capacity(private_keys.len());
+    for private_key in private_keys.iter_mut() {
+        if let Some(text) = age_key_text(private_key, crate::age_file::IDENTITY_PREFIX) {
+            identities.extend(crate::age_file::parse_identities(text)?);
+        } else {
+            key_format.unpack_input(private_key).map_err(SigliError::MalformedKey)?;
+            let text = crate::age_file::identity_string(private_key)?;
+            identities.extend(crate::age_file::parse_identities(&text)?);
+        }
+    }
+
+    *data = crate::age_file::decrypt(&identities, passphrase, data)?;
+
+    select_format(output_format).pack_output(data);
+
+    Ok(())
+}
+
+
+/// Whether `data` is an OpenSSH Ed25519 public key line, as found in `authorized_keys` or
+/// `~/.ssh/id_ed25519.pub`.
+pub fn is_ssh_public_key(data: &[u8]) -> bool {
+    crate::ssh::is_public_key(data)
+}
+
+
+/// Whether `data` is an OpenSSH private key file, such as `~/.ssh/id_ed25519`.
+pub fn is_ssh_private_key(data: &[u8]) -> bool {
+    crate::ssh::is_private_key(data)
+}
+
+
+/// Whether an OpenSSH private key is protected by a passphrase.
+pub fn ssh_private_key_is_encrypted(private_key: &[u8]) -> Result<bool, SigliError> {
+    Ok(crate::ssh::is_encrypted(private_key)?)
+}
+
+
+/// Convert an OpenSSH Ed25519 public key line into an x25519 public key.
+///
+/// The result can be used wherever an x25519 public key from `generate_key_pair` is accepted,
+/// such as `encrypt_to_recipient` with `KemType::X25519`. Messages sent to it are opened with
+/// the matching OpenSSH private key converted by `x25519_from_ssh_private_key`.
+///
+/// # Arguments
+///
+/// * `key_format` - Format used to pack the x25519 public key.
+/// * `public_key` - OpenSSH public key line, `ssh-ed25519 AAAA... comment`.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// use sigli::{x25519_from_ssh_public_key, FormatType};
+///
+/// let public_key = x25519_from_ssh_public_key(
+///     FormatType::Hex,
+///     b"ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIANdb6FoSUiuUa1Anm3DIrvFHe4Q8byuN00MkafgH/QK alice"
+/// )?;
+/// assert_eq!(80, public_key.len());
+/// # Ok(())
+/// # }
+/// ```
+pub fn x25519_from_ssh_public_key(
+    key_format: FormatType,
+    public_key: &[u8],
+) -> Result<Vec<u8>, SigliError> {
+    let mut key = crate::ssh::x25519_public_key(public_key)?;
+
+    select_format(key_format).pack_output(&mut key);
+
+    Ok(key)
+}
+
+
+/// Convert an OpenSSH Ed25519 private key file into an x25519 private key.
+///
+/// The result can be used wherever an x25519 private key from `generate_key_pair` is
+/// accepted, such as `decrypt_as_recipient` with `KemType::X25519`.
+///
+/// # Arguments
+///
+/// * `key_format` - Format used to pack the x25519 private key.
+/// * `private_key` - OpenSSH private key file.
+/// * `passphrase` - Passphrase, needed if the private key is encrypted.
+pub fn x25519_from_ssh_private_key(
+    key_format: FormatType,
+    private_key: &[u8],
+    passphrase: Option<&[u8]>,
+) -> Result<Vec<u8>, SigliError> {
+    let mut key = crate::ssh::x25519_private_key(private_key, passphrase)?;
+
+    select_format(key_format).pack_output(&mut key);
+
+    Ok(key)
+}
@@ -0,0 +1,89 @@
+use spake2::{Ed25519Group, Identity, Password, Spake2};
+
+/// Identity shared by both sides, since neither side has a distinct role.
+const IDENTITY: &[u8] = b"sigli pake";
+
+/// Byte SPAKE2 puts in front of each symmetric message. Both sides use the same one, so
+/// it is left out of the messages to keep them short.
+const SYMMETRIC_SIDE: u8 = b'S';
+
+pub const MESSAGE_NUM_BYTES: usize = 32;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PakeError {
+    /// Message from the other side is not a valid SPAKE2 message.
+    MalformedMessage,
+}
+
+/// One side of a SPAKE2 exchange, waiting for the message from the other side.
+pub struct PakeExchange {
+    state: Spake2<Ed25519Group>,
+}
+
+/// Start an exchange, returning the 32 byte message to send to the other side.
+pub fn start(code: &[u8]) -> (PakeExchange, Vec<u8>) {
+    let (state, mut message) =
+        Spake2::<Ed25519Group>::start_symmetric(&Password::new(code), &Identity::new(IDENTITY));
+    message.remove(0);
+    (PakeExchange { state }, message)
+}
+
+/// Finish an exchange with the message from the other side, returning the 32 byte shared
+/// secret. Both sides get the same secret only if they used the same code.
+pub fn finish(exchange: PakeExchange, message: &[u8]) -> Result<Vec<u8>, PakeError> {
+    if message.len() != MESSAGE_NUM_BYTES {
+        return Err(PakeError::MalformedMessage);
+    }
+    let mut full_message = vec![SYMMETRIC_SIDE];
+    full_message.extend_from_slice(message);
+    exchange
+        .state
+        .finish(&full_message)
+        .map_err(|_| PakeError::MalformedMessage)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_code_gives_same_secret() {
+        let (exchange_a, message_a) = start(b"purple monkey");
+        let (exchange_b, message_b) = start(b"purple monkey");
+
+        let secret_a = finish(exchange_a, &message_b).unwrap();
+        let secret_b = finish(exchange_b, &message_a).unwrap();
+
+        assert_eq!(secret_a, secret_b);
+        assert_eq!(32, secret_a.len());
+    }
+
+    #[test]
+    fn different_codes_give_different_secrets() {
+        let (exchange_a, message_a) = start(b"purple monkey");
+        let (exchange_b, message_b) = start(b"purple donkey");
+
+        let secret_a = finish(exchange_a, &message_b).unwrap();
+        let secret_b = finish(exchange_b, &message_a).unwrap();
+
+        assert_ne!(secret_a, secret_b);
+    }
+
+    #[test]
+    fn messages_are_short_and_fresh() {
+        let (_, message_a) = start(b"purple monkey");
+        let (_, message_b) = start(b"purple monkey");
+
+        assert_eq!(MESSAGE_NUM_BYTES, message_a.len());
+        assert_ne!(message_a, message_b);
+    }
+
+    #[test]
+    fn can_not_finish_with_wrong_length_message() {
+        let (exchange, _) = start(b"purple monkey");
+
+        let result = finish(exchange, &[0u8; 31]).unwrap_err();
+
+        assert_eq!(PakeError::MalformedMessage, result);
+    }
+}
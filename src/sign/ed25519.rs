@@ -0,0 +1,171 @@
+use ed25519_dalek::{
+    Signature, Signer as _, SigningKey, VerifyingKey, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH,
+    SIGNATURE_LENGTH,
+};
+use rand::{thread_rng, RngCore};
+
+use super::{SignError, SignInfo, Signer};
+
+pub struct Ed25519Signer {}
+
+pub const SIGN_NAME: &str = "ed25519";
+
+impl Ed25519Signer {
+    fn signing_key_from(data: &[u8]) -> Result<SigningKey, SignError> {
+        if data.len() != SECRET_KEY_LENGTH {
+            return Err(SignError::PrivateKeyWrongLength {
+                expected_length: SECRET_KEY_LENGTH,
+                actual_length: data.len(),
+            });
+        };
+        let mut seed = [0u8; SECRET_KEY_LENGTH];
+        seed.copy_from_slice(data);
+        Ok(SigningKey::from_bytes(&seed))
+    }
+
+    fn verifying_key_from(data: &[u8]) -> Result<VerifyingKey, SignError> {
+        if data.len() != PUBLIC_KEY_LENGTH {
+            return Err(SignError::PublicKeyWrongLength {
+                expected_length: PUBLIC_KEY_LENGTH,
+                actual_length: data.len(),
+            });
+        };
+        let mut public_key = [0u8; PUBLIC_KEY_LENGTH];
+        public_key.copy_from_slice(data);
+        VerifyingKey::from_bytes(&public_key).map_err(|_| SignError::InvalidPublicKey)
+    }
+}
+
+impl Signer for Ed25519Signer {
+    fn info(&self) -> SignInfo {
+        SignInfo {
+            private_key_num_bytes: SECRET_KEY_LENGTH,
+            public_key_num_bytes: PUBLIC_KEY_LENGTH,
+            signature_num_bytes: SIGNATURE_LENGTH,
+        }
+    }
+
+    fn generate_key_pair_data(&self) -> (Vec<u8>, Vec<u8>) {
+        let mut seed = [0u8; SECRET_KEY_LENGTH];
+        thread_rng().fill_bytes(&mut seed);
+        let signing_key = SigningKey::from_bytes(&seed);
+        (seed.to_vec(), signing_key.verifying_key().as_bytes().to_vec())
+    }
+
+    fn sign(&self, private_key: &[u8], message: &[u8]) -> Result<Vec<u8>, SignError> {
+        let signing_key = Self::signing_key_from(private_key)?;
+        Ok(signing_key.sign(message).to_bytes().to_vec())
+    }
+
+    fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), SignError> {
+        let verifying_key = Self::verifying_key_from(public_key)?;
+        let signature =
+            Signature::from_slice(signature).map_err(|_| SignError::SignatureWrongLength)?;
+        verifying_key
+            .verify_strict(message, &signature)
+            .map_err(|_| SignError::VerificationFailed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn raw_data() -> Vec<u8> {
+        vec![0xAB, 0x01, 0x02, 0x22, 0x23, 0x43]
+    }
+
+    #[test]
+    fn matches_rfc_8032_test_1() {
+        let signer = Ed25519Signer {};
+        let private_key =
+            hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")
+                .unwrap();
+        let public_key =
+            hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+                .unwrap();
+
+        let signature = signer.sign(&private_key, &[]).unwrap();
+
+        assert_eq!(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+             5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            hex::encode(&signature)
+        );
+        assert_eq!(
+            public_key,
+            Ed25519Signer::signing_key_from(&private_key)
+                .unwrap()
+                .verifying_key()
+                .as_bytes()
+                .to_vec()
+        );
+        signer.verify(&public_key, &[], &signature).unwrap();
+    }
+
+    #[test]
+    fn can_round_trip() {
+        let signer = Ed25519Signer {};
+        let (private_key, public_key) = signer.generate_key_pair_data();
+
+        let signature = signer.sign(&private_key, &raw_data()).unwrap();
+
+        signer.verify(&public_key, &raw_data(), &signature).unwrap();
+    }
+
+    #[test]
+    fn generates_different_key_pairs() {
+        let signer = Ed25519Signer {};
+        assert_ne!(signer.generate_key_pair_data(), signer.generate_key_pair_data())
+    }
+
+    #[test]
+    fn can_not_verify_altered_message() {
+        let signer = Ed25519Signer {};
+        let (private_key, public_key) = signer.generate_key_pair_data();
+        let signature = signer.sign(&private_key, &raw_data()).unwrap();
+        let mut message = raw_data();
+        message[0] ^= 0x01;
+
+        let result = signer.verify(&public_key, &message, &signature).unwrap_err();
+
+        assert_eq!(SignError::VerificationFailed, result);
+    }
+
+    #[test]
+    fn can_not_verify_with_other_public_key() {
+        let signer = Ed25519Signer {};
+        let (private_key, _) = signer.generate_key_pair_data();
+        let (_, other_public_key) = signer.generate_key_pair_data();
+        let signature = signer.sign(&private_key, &raw_data()).unwrap();
+
+        let result = signer.verify(&other_public_key, &raw_data(), &signature).unwrap_err();
+
+        assert_eq!(SignError::VerificationFailed, result);
+    }
+
+    #[test]
+    fn can_not_verify_wrong_length_signature() {
+        let signer = Ed25519Signer {};
+        let (_, public_key) = signer.generate_key_pair_data();
+
+        let result = signer.verify(&public_key, &raw_data(), &[0u8; 63]).unwrap_err();
+
+        assert_eq!(SignError::SignatureWrongLength, result);
+    }
+
+    #[test]
+    fn can_not_use_wrong_length_private_key() {
+        let signer = Ed25519Signer {};
+
+        let result = signer.sign(&[0u8; 31], &raw_data()).unwrap_err();
+
+        assert_eq!(
+            SignError::PrivateKeyWrongLength {
+                expected_length: 32,
+                actual_length: 31
+            },
+            result
+        )
+    }
+}
@@ -0,0 +1,95 @@
+use std::str::FromStr;
+
+mod ed25519;
+
+pub const DEFAULT_SIGN_NAME: &str = ed25519::SIGN_NAME;
+
+pub static SIGN_NAMES: &[&str] = &[ed25519::SIGN_NAME];
+
+/// Signature scheme used to sign messages that anyone with the public key can verify.
+pub enum SignType {
+
+    /// Ed25519 (RFC 8032).
+    ///
+    /// Implemented using [dalek-cryptography]: https://github.com/dalek-cryptography/curve25519-dalek
+    ///
+    /// Signatures are deterministic, so signing the same message twice gives the same
+    /// signature. Verification is strict, rejecting malleable signatures and weak keys.
+    ///
+    /// - 32 byte private key (the RFC 8032 seed).
+    /// - 32 byte public key.
+    /// - 64 byte signature.
+    Ed25519,
+}
+
+impl FromStr for SignType {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            ed25519::SIGN_NAME => Ok(SignType::Ed25519),
+            _ => Err("no match"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignError {
+    PrivateKeyWrongLength {
+        expected_length: usize,
+        actual_length: usize,
+    },
+    PublicKeyWrongLength {
+        expected_length: usize,
+        actual_length: usize,
+    },
+    /// Signed message is shorter than a signature, or a detached signature is the wrong length.
+    SignatureWrongLength,
+    /// Public key is not a valid point.
+    InvalidPublicKey,
+    VerificationFailed,
+}
+
+/// Sizes of the keys and signatures used by a signature scheme.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SignInfo {
+    pub private_key_num_bytes: usize,
+    pub public_key_num_bytes: usize,
+    pub signature_num_bytes: usize,
+}
+
+pub trait Signer {
+    fn info(&self) -> SignInfo;
+
+    /// Generate a new key pair, returned as private key data then public key data.
+    fn generate_key_pair_data(&self) -> (Vec<u8>, Vec<u8>);
+
+    /// Sign `message` with `private_key`, returning the signature.
+    fn sign(&self, private_key: &[u8], message: &[u8]) -> Result<Vec<u8>, SignError>;
+
+    /// Check that `signature` was made over `message` by the owner of `public_key`.
+    fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), SignError>;
+}
+
+pub fn select_signer(name: SignType) -> Box<dyn Signer> {
+    match name {
+        SignType::Ed25519 => Box::new(ed25519::Ed25519Signer {}),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reported_sizes_match_generated_keys() {
+        for name in SIGN_NAMES {
+            let signer = select_signer(SignType::from_str(name).unwrap());
+            let (private_key, public_key) = signer.generate_key_pair_data();
+            let signature = signer.sign(&private_key, &[0xAB, 0x01]).unwrap();
+
+            assert_eq!(signer.info().private_key_num_bytes, private_key.len(), "{}", name);
+            assert_eq!(signer.info().public_key_num_bytes, public_key.len(), "{}", name);
+            assert_eq!(signer.info().signature_num_bytes, signature.len(), "{}", name);
+        }
+    }
+}